@@ -0,0 +1,142 @@
+use clap::{Args, Parser, Subcommand};
+use practicas::par::{Preprocessing, Scaling};
+
+
+/// Metaheuristics for the constrained clustering problem (PAR)
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run an algorithm over one instance
+    Run(RunArgs),
+    /// Run the experiments described in a manifest
+    Batch(BatchArgs),
+    /// Score a partition saved by `run` or produced by another tool, listing its violated constraints
+    Evaluate(EvaluateArgs),
+    /// Generate a synthetic instance
+    Generate(GenerateArgs),
+}
+
+/// Files of a problem instance
+#[derive(Args)]
+pub struct InstanceArgs {
+    /// Data file, one comma-separated point per line
+    #[arg(short, long)]
+    pub data: String,
+    /// Constraints file, either a matrix or a list of `i,j,±1` lines
+    #[arg(short, long)]
+    pub constraints: String,
+    /// Number of clusters
+    #[arg(short, value_parser = clap::value_parser!(u64).range(1..))]
+    pub k: u64,
+    /// Scaling of each feature of the data, fitted on the data itself
+    #[arg(long, default_value = "none", value_parser = clap::builder::PossibleValuesParser::new(Scaling::NAMES))]
+    pub scaling: String,
+    /// Remove the features that have the same value in every point
+    #[arg(long)]
+    pub drop_constant: bool,
+    /// Distance between points and centroids
+    #[arg(short, long, default_value = "euclidean", value_parser = clap::builder::PossibleValuesParser::new(practicas::par::Metric::NAMES))]
+    pub metric: String,
+    /// Multiplier of the weight of every violated must-link
+    #[arg(long, default_value_t = 1.0)]
+    pub must_link_penalty: f64,
+    /// Multiplier of the weight of every violated cannot-link
+    #[arg(long, default_value_t = 1.0)]
+    pub cannot_link_penalty: f64,
+    /// Ground-truth file with the class of each point, to score partitions with ARI, NMI and purity
+    #[arg(short, long)]
+    pub ground_truth: Option<String>,
+}
+
+impl InstanceArgs {
+    /// Preprocessing of the data of the instance
+    pub fn preprocessing(&self) -> Preprocessing {
+        Preprocessing {
+            scaling: Scaling::from_name(&self.scaling).unwrap_or_default(),
+            drop_constant: self.drop_constant,
+        }
+    }
+
+    /// Multipliers of the weights of violated must-links and cannot-links
+    pub fn penalties(&self) -> (f64, f64) {
+        (self.must_link_penalty, self.cannot_link_penalty)
+    }
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// Algorithm to run
+    #[arg(short, long, value_parser = clap::builder::PossibleValuesParser::new(practicas::par::Registry::default().names()))]
+    pub algorithm: String,
+    #[command(flatten)]
+    pub instance: InstanceArgs,
+    /// Name of the instance in the results (defaults to the name of the constraints file)
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// Comma-separated random seeds, one execution each
+    #[arg(short, long, value_delimiter = ',', default_value = "1")]
+    pub seeds: Vec<u64>,
+    /// Maximum number of fitness evaluations
+    #[arg(short, long)]
+    pub budget: Option<usize>,
+    /// Directory where results/<algorithm>/<instance>.csv is written
+    #[arg(short, long, default_value = "results")]
+    pub output: String,
+    /// Number of executions run at the same time (0 uses every core)
+    #[arg(short, long, default_value_t = 1)]
+    pub jobs: usize,
+    /// Record the convergence of each execution every this many evaluations, in results/<algorithm>/<instance>_trace.csv
+    #[arg(short, long)]
+    pub trace: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// TOML manifest with the instances, algorithms, seeds and output directories
+    pub manifest: String,
+    /// Number of executions run at the same time (0 uses every core), instead of the one of the manifest
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct EvaluateArgs {
+    #[command(flatten)]
+    pub instance: InstanceArgs,
+    /// Label file: the cluster of each point, one per line, after optional `# key: value` metadata lines
+    #[arg(short, long)]
+    pub labels: String,
+}
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Prefix of the generated files (<prefix>.dat, <prefix>.const and <prefix>.labels)
+    pub prefix: String,
+    /// Number of points
+    #[arg(short = 'n', long, default_value_t = 200, value_parser = clap::value_parser!(u64).range(1..))]
+    pub points: u64,
+    /// Dimension of the points
+    #[arg(short, long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+    pub dimension: u64,
+    /// Number of clusters
+    #[arg(short, default_value_t = 3, value_parser = clap::value_parser!(u64).range(1..))]
+    pub k: u64,
+    /// Standard deviation of each cluster
+    #[arg(long, default_value_t = 0.05)]
+    pub spread: f64,
+    /// Fraction of the points whose pairs are constrained
+    #[arg(short, long, default_value_t = 0.1)]
+    pub ratio: f64,
+    /// Write the constraints as a list instead of a matrix
+    #[arg(long)]
+    pub list: bool,
+    /// Random seed
+    #[arg(short, long, default_value_t = 1)]
+    pub seed: u64,
+}
@@ -0,0 +1,21 @@
+//! Metaheuristics for the constrained clustering problem (PAR)
+//!
+//! A [`par::Problem`] is loaded from a data file and a constraints file, and any [`par::Solver`]
+//! built by a [`par::Registry`] (or any algorithm of [`par::algorithms`] called directly) finds a
+//! partition of its points into `k` clusters, minimizing the general deviation plus the number of
+//! violated constraints weighted by `lambda`.
+//!
+//! ```no_run
+//! use practicas::par::{Problem, Registry};
+//! use rand::SeedableRng;
+//! use rand_pcg::Pcg64;
+//!
+//! let problem = Problem::load("instances/zoo_set.dat", "instances/zoo_set_const_10.const", 7).unwrap();
+//! let solver = Registry::default().create("es", None, None).unwrap();
+//! let result = solver.solve(&problem, &mut Pcg64::seed_from_u64(1));
+//!
+//! println!("{} (infeasibility {})", result.aggregate, result.infeasibility);
+//! ```
+
+/// PAR problem, partitions and algorithms
+pub mod par;
@@ -1,98 +1,320 @@
-mod par;
-use par::Problem;
-use par::ExecutionRecord;
-use par::algorithms::{greedy, local_search};
+mod cli;
+mod manifest;
+use practicas::par::Problem;
+use practicas::par::{ExecutionRecord, TraceRecord, Partition, PartitionInfo, ConstraintKind, Contingency, Registry, Solver, SolveResult, Preprocessing};
+use practicas::par::generator::{generate, GeneratorParams};
+use cli::{Cli, Command, RunArgs, BatchArgs, EvaluateArgs, GenerateArgs};
+use manifest::Manifest;
 
+use clap::Parser;
 use rand::SeedableRng;
 use rand_pcg::Pcg64;
-use std::time::Instant;
-use std::collections::HashMap;
 use colored::*;
-use std::io::{stdout, Write};
-use std::env;
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::path::Path;
+use std::process;
+use std::fs::create_dir_all;
 
 
 fn main() {
-    // Command-line arguments
-    let args: Vec<String> = env::args().collect();
-
-    // Map containing problem instances
-    let mut instances = HashMap::new();
-    let mut seeds = Vec::new();
-
-    if args.len() == 2 && args[1] == "all" {
-        // Initialize random seed
-        seeds = vec![4, 7, 2, 1, 3];
-
-        // Zoo
-        instances.insert("zoo10", Problem::from_files("instances/zoo_set.dat", "instances/zoo_set_const_10.const", 7)); 
-        instances.insert("zoo20", Problem::from_files("instances/zoo_set.dat", "instances/zoo_set_const_20.const", 7));
-
-        // Bupa
-        instances.insert("bupa10", Problem::from_files("instances/bupa_set.dat", "instances/bupa_set_const_10.const", 16));
-        instances.insert("bupa20", Problem::from_files("instances/bupa_set.dat", "instances/bupa_set_const_20.const", 16));
-
-        // Glass
-        instances.insert("glass10", Problem::from_files("instances/glass_set.dat", "instances/glass_set_const_10.const", 7));
-        instances.insert("glass20", Problem::from_files("instances/glass_set.dat", "instances/glass_set_const_20.const", 7));
-    }
-    else if args.len() == 6 {
-        let data_file = &args[1];
-        let constraints_file = &args[2];
-        let results_file = &args[3];
-        let number_of_clusters = args[4].parse::<usize>().unwrap();
-        seeds = vec![args[5].parse::<u64>().unwrap()];
-
-        instances.insert(results_file, Problem::from_files(data_file, constraints_file, number_of_clusters));
-    }
-
-    
-
-    // Execute greedy for every instance five times, saving each one in its respective csv file
-    for (key, instance) in instances.iter() {
-        println!("Executing greedy for instance {}", key);
-        let mut wtr = csv::Writer::from_path(format!("results/greedy/{}.csv", key)).unwrap();
-        for seed in seeds.iter() {
-            print!("Seed {}: ", seed);
-            stdout().flush().unwrap();
-            let mut rng = Pcg64::seed_from_u64(*seed);
-            let now = Instant::now();
-            let (_partition, aggr, inf, dev) = greedy(&instance, &mut rng);
-            let time = now.elapsed().as_millis();
-        
-            wtr.serialize(ExecutionRecord {
-                instance: *seed as usize,
-                aggregate: aggr,
-                infeasibility: inf,
-                general_deviation: dev,
-                time: time,
-            }).unwrap();
-            print!("{}\n", "OK".bold().green());
+    let result = match Cli::parse().command {
+        Command::Run(args) => run(args),
+        Command::Batch(args) => batch(args),
+        Command::Evaluate(args) => evaluate(args),
+        Command::Generate(args) => generate_instance(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}: {}", "error".red().bold(), e);
+        process::exit(1);
+    }
+}
+
+/// Prints whether some files have been read correctly and returns the result
+fn report<T, E: ToString>(result: Result<T, E>) -> Result<T, String> {
+    match result {
+        Ok(_) => println!("{}", "OK".green().bold()),
+        Err(_) => println!("{}", "ERROR".red().bold()),
+    }
+
+    result.map_err(|e| e.to_string())
+}
+
+/// Loads a problem with the given preprocessing, distance metric and multipliers of the must-link
+/// and cannot-link weights and, if given, its ground truth
+fn load_problem(data: &str, constraints: &str, k: usize, preprocessing: &Preprocessing, metric: &str, penalties: (f64, f64), ground_truth: Option<&str>) -> Result<Problem, String> {
+    print!("Reading data from {} and constraints from {}: ", data, constraints);
+    let mut problem = report(Problem::load_preprocessed(data, constraints, k, preprocessing))?;
+    if *preprocessing != Preprocessing::default() {
+        let transform = problem.transform();
+        println!("Preprocessing data: {} scaling, {} of {} features kept", transform.scaling, transform.columns.len(), transform.dimension);
+    }
+
+    problem.set_metric_name(metric).map_err(|e| e.to_string())?;
+    problem.set_penalties(penalties.0, penalties.1).map_err(|e| e.to_string())?;
+    if let Some(file) = ground_truth {
+        print!("Reading ground truth from {}: ", file);
+        report(problem.load_ground_truth(file))?;
+    }
+
+    Ok(problem)
+}
+
+/// `run` subcommand: executes an algorithm over one instance
+fn run(args: RunArgs) -> Result<(), String> {
+    let instance = &args.instance;
+    let name = args.name.unwrap_or_else(|| {
+        Path::new(&instance.constraints).file_stem().map_or(instance.constraints.clone(), |s| s.to_string_lossy().into_owned())
+    });
+
+    let problem = load_problem(&instance.data, &instance.constraints, instance.k as usize, &instance.preprocessing(), &instance.metric, instance.penalties(), instance.ground_truth.as_deref())?;
+    let instances = vec![(name, problem)];
+
+    let solver = Registry::default().create(&args.algorithm, args.budget, None)?;
+    let results_dir = format!("{}/{}", args.output, solver.name());
+    execute(solver.as_ref(), &results_dir, &instances, &args.seeds, args.trace, &thread_pool(args.jobs)?)
+}
+
+/// `batch` subcommand: executes the algorithms of a manifest over its instances
+fn batch(args: BatchArgs) -> Result<(), String> {
+    let manifest = Manifest::from_file(&args.manifest)?;
+
+    let mut instances = Vec::new();
+    for entry in manifest.instances.iter() {
+        let problem = load_problem(&entry.data, &entry.constraints, entry.k, &entry.preprocessing()?, entry.metric(), entry.penalties(), entry.ground_truth.as_deref())?;
+        instances.push((entry.name.clone(), problem));
+    }
+
+    // Every configuration is checked before running any of them
+    let registry = Registry::default();
+    let mut solvers = Vec::new();
+    for entry in manifest.algorithms.iter() {
+        let solver = registry.create(&entry.algorithm, entry.budget, entry.params.as_ref())
+            .map_err(|e| format!("{}: {}: {}", args.manifest, entry.name(), e))?;
+        solvers.push((entry, solver));
+    }
+
+    let pool = thread_pool(args.jobs.unwrap_or(manifest.workers))?;
+    for (entry, solver) in solvers {
+        execute(solver.as_ref(), &entry.output(&manifest.output), &instances, &manifest.seeds, manifest.trace, &pool)?;
+    }
+
+    Ok(())
+}
+
+/// `evaluate` subcommand: scores a label file, which may come from another tool
+fn evaluate(args: EvaluateArgs) -> Result<(), String> {
+    let instance = &args.instance;
+    let problem = load_problem(&instance.data, &instance.constraints, instance.k as usize, &instance.preprocessing(), &instance.metric, instance.penalties(), instance.ground_truth.as_deref())?;
+
+    let (partition, info) = Partition::load(&args.labels, &problem).map_err(|e| e.to_string())?;
+    if let Some(fitness) = info.fitness {
+        println!("Saved aggregate: {}", fitness);
+    }
+    if let Some(metric) = info.metric.filter(|m| m != problem.metric().name()) {
+        println!("The partition was saved with the {} metric, but it is scored with the {} one", metric, problem.metric());
+    }
+
+    let report = problem.report(&partition);
+    println!("Aggregate: {}", report.aggregate);
+    println!("Infeasibility: {}", report.infeasibility);
+    println!("General deviation: {}", report.general_deviation);
+
+    println!("\nCluster  Size  Intra-cluster distance  ML violations  CL violations");
+    for (i, cluster) in report.clusters.iter().enumerate() {
+        println!("{:>7}  {:>4}  {:>22.6}  {:>13}  {:>13}",
+            i, cluster.size, cluster.intra_cluster_distance, cluster.must_link_violations, cluster.cannot_link_violations);
+    }
+
+    let internal = problem.internal_scores(&partition);
+    println!("\nSilhouette: {}", internal.silhouette);
+    println!("Davies–Bouldin index: {}", internal.davies_bouldin);
+    println!("Calinski–Harabasz index: {}", internal.calinski_harabasz);
+
+    if let (Some(scores), Some(classes)) = (problem.external_scores(&partition), problem.ground_truth()) {
+        println!("\nAdjusted Rand index: {}", scores.adjusted_rand_index);
+        println!("Normalized mutual information: {}", scores.normalized_mutual_information);
+        println!("Purity: {}", scores.purity);
+
+        println!("\nContingency matrix (clusters in rows, classes in columns):");
+        for (i, row) in Contingency::new(&partition, classes).counts().iter().enumerate() {
+            let counts: Vec<String> = row.iter().map(|c| format!("{:>5}", c)).collect();
+            println!("{:>7}  {}", i, counts.join(""));
         }
-        wtr.flush().unwrap();
-    }
-
-    // Execute local search for every instance five times, saving each one in its respective csv file
-    for (key, instance) in instances.iter() {
-        println!("Executing local search for instance {}", key);
-        let mut wtr = csv::Writer::from_path(format!("results/local-search/{}.csv", key)).unwrap();
-        for seed in seeds.iter() {
-            print!("Seed {}: ", seed);
-            stdout().flush().unwrap();
-            let mut rng = Pcg64::seed_from_u64(*seed);
-            let now = Instant::now();
-            let (_partition, aggr, inf, dev) = local_search(&instance, &mut rng);
-            let time = now.elapsed().as_millis();
-        
-            wtr.serialize(ExecutionRecord {
-                instance: *seed as usize,
-                aggregate: aggr,
-                infeasibility: inf,
-                general_deviation: dev,
-                time: time,
-            }).unwrap();
-            print!("{}\n", "OK".bold().green());
+    }
+
+    if !report.violations.is_empty() {
+        println!("\nViolated constraints:");
+        for violation in report.violations.iter() {
+            let kind = match violation.kind {
+                ConstraintKind::MustLink => "ML",
+                ConstraintKind::CannotLink => "CL",
+            };
+            println!("{} {} {} (clusters {} and {}, penalty {})",
+                kind, violation.first, violation.second, violation.clusters.0, violation.clusters.1, violation.penalty);
+        }
+    }
+
+    Ok(())
+}
+
+/// `generate` subcommand: writes a synthetic instance
+fn generate_instance(args: GenerateArgs) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&args.ratio) {
+        return Err(format!("the constrained ratio must be between 0 and 1, found {}", args.ratio));
+    }
+    if args.k > args.points {
+        return Err(format!("cannot make {} clusters out of {} points", args.k, args.points));
+    }
+
+    let params = GeneratorParams {
+        points: args.points as usize,
+        dimension: args.dimension as usize,
+        k: args.k as usize,
+        spread: args.spread,
+        labelled_ratio: args.ratio,
+    };
+    let instance = generate(&params, &mut Pcg64::seed_from_u64(args.seed));
+
+    let data_file = format!("{}.dat", args.prefix);
+    let labels_file = format!("{}.labels", args.prefix);
+    let constraints_file = if args.list { format!("{}.list", args.prefix) } else { format!("{}.const", args.prefix) };
+
+    let written = instance.write_data(&data_file)
+        .and_then(|_| instance.write_labels(&labels_file))
+        .and_then(|_| if args.list { instance.write_list(&constraints_file) } else { instance.write_matrix(&constraints_file) });
+    written.map_err(|e| format!("{}: {}", args.prefix, e))?;
+
+    println!("Generated {}, {} and {} ({} constraints)", data_file, constraints_file, labels_file, instance.constraints.len());
+
+    Ok(())
+}
+
+/// Thread pool that runs the executions
+/// - workers: usize - Number of threads, or 0 to use every core
+fn thread_pool(workers: usize) -> Result<ThreadPool, String> {
+    ThreadPoolBuilder::new().num_threads(workers).build().map_err(|e| e.to_string())
+}
+
+/// Executes a solver for every instance and seed, saving the results of each instance in
+/// `<results_dir>/<instance>.csv`, the final partition of each seed in
+/// `<results_dir>/<instance>_partition_<seed>.labels`, and its convergence traces in
+/// `<results_dir>/<instance>_trace.csv` if `trace` is the interval between two recorded states
+/// Executions are spread over the threads of `pool`, each one with its own generator seeded with
+/// its seed, so the results do not depend on the number of threads. Rows are written in seed order
+fn execute(solver: &dyn Solver, results_dir: &str, instances: &[(String, Problem)], seeds: &[u64], trace: Option<usize>, pool: &ThreadPool) -> Result<(), String> {
+    create_dir_all(results_dir).map_err(|e| format!("{}: {}", results_dir, e))?;
+    println!("Executing {} for {} instances and {} seeds", solver.name(), instances.len(), seeds.len());
+
+    let runs: Vec<(&String, &Problem, u64)> = instances.iter()
+        .flat_map(|(key, instance)| seeds.iter().map(move |&seed| (key, instance, seed)))
+        .collect();
+
+    // Final partitions are scored after each execution, out of its time
+    let results: Vec<(SolveResult, ExecutionRecord)> = pool.install(|| {
+        runs.par_iter().map(|&(key, instance, seed)| {
+            let mut rng = Pcg64::seed_from_u64(seed);
+            let result = match trace {
+                Some(interval) => solver.solve_traced(instance, interval, &mut rng),
+                None => solver.solve(instance, &mut rng),
+            };
+            let record = ExecutionRecord::new(seed, instance, &result);
+            println!("Instance {}, seed {}: {}", key, seed, "OK".bold().green());
+            (result, record)
+        }).collect()
+    });
+
+    for ((key, problem), results) in instances.iter().zip(results.chunks(seeds.len().max(1))) {
+        let results_file = format!("{}/{}.csv", results_dir, key);
+        let mut wtr = csv::Writer::from_path(&results_file).map_err(|e| format!("{}: {}", results_file, e))?;
+        for (seed, (result, record)) in seeds.iter().zip(results) {
+            wtr.serialize(record).map_err(|e| format!("{}: {}", results_file, e))?;
+
+            let partition_file = format!("{}/{}_partition_{}.labels", results_dir, key, seed);
+            let info = PartitionInfo {
+                instance: Some(key.clone()),
+                k: Some(result.partition.clusters().len()),
+                seed: Some(*seed),
+                algorithm: Some(solver.name().to_string()),
+                fitness: Some(result.aggregate),
+                metric: Some(problem.metric().to_string()),
+            };
+            result.partition.save(&partition_file, &info).map_err(|e| format!("{}: {}", partition_file, e))?;
+        }
+        wtr.flush().map_err(|e| format!("{}: {}", results_file, e))?;
+
+        if trace.is_some() {
+            let trace_file = format!("{}/{}_trace.csv", results_dir, key);
+            let mut wtr = csv::Writer::from_path(&trace_file).map_err(|e| format!("{}: {}", trace_file, e))?;
+            for (seed, (result, _)) in seeds.iter().zip(results) {
+                for point in result.trace.iter().flatten() {
+                    wtr.serialize(TraceRecord::new(*seed, point)).map_err(|e| format!("{}: {}", trace_file, e))?;
+                }
+            }
+            wtr.flush().map_err(|e| format!("{}: {}", trace_file, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{read_dir, read_to_string};
+
+    /// Files written by `execute` in a directory, with the time column of the CSV files removed
+    fn outputs(dir: &str) -> Vec<(String, String)> {
+        let mut files: Vec<(String, String)> = read_dir(dir).unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                let content = read_to_string(&path).unwrap();
+                let content = if path.extension().is_some_and(|e| e == "csv") {
+                    let time = content.lines().next().unwrap().split(',').position(|f| f == "Time").unwrap();
+                    content.lines()
+                        .map(|line| line.split(',').enumerate().filter(|&(i, _)| i != time).map(|(_, f)| f).collect::<Vec<_>>().join(","))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+                else {
+                    content
+                };
+                (path.file_name().unwrap().to_string_lossy().into_owned(), content)
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn results_do_not_depend_on_the_number_of_workers() {
+        let dir = std::env::temp_dir().join(format!("practicas-{}-workers", std::process::id()));
+        let prefix = dir.join("blobs").to_string_lossy().into_owned();
+        create_dir_all(&dir).unwrap();
+
+        let params = GeneratorParams { points: 60, dimension: 2, k: 3, spread: 0.05, labelled_ratio: 0.3 };
+        let instance = generate(&params, &mut Pcg64::seed_from_u64(7));
+        instance.write_data(&format!("{}.dat", prefix)).unwrap();
+        instance.write_list(&format!("{}.list", prefix)).unwrap();
+        let problem = Problem::load(&format!("{}.dat", prefix), &format!("{}.list", prefix), 3).unwrap();
+        let instances = vec![("blobs".to_string(), problem)];
+        let seeds = [1, 2, 3, 4, 5];
+
+        let registry = Registry::default();
+        for algorithm in ["es", "agg-un", "ils"] {
+            let solver = registry.create(algorithm, Some(3000), None).unwrap();
+            let mut runs = Vec::new();
+            for workers in [1, 3] {
+                let results_dir = dir.join(format!("{}-{}", algorithm, workers)).to_string_lossy().into_owned();
+                execute(solver.as_ref(), &results_dir, &instances, &seeds, Some(500), &thread_pool(workers).unwrap()).unwrap();
+                runs.push(outputs(&results_dir));
+            }
+
+            assert_eq!(runs[0], runs[1], "{} gives different results with more workers", algorithm);
         }
-        wtr.flush().unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,206 @@
+use practicas::par::{Preprocessing, Scaling};
+use serde_derive::Deserialize;
+use std::fs::read_to_string;
+
+
+/// Experiment manifest: instances, algorithms and seeds of a batch of executions
+/// - seeds: Vec<u64> - Random seeds, one execution each
+/// - output: String - Directory where results/<algorithm>/<instance>.csv is written
+/// - workers: usize - Number of executions run at the same time (0 uses every core)
+/// - trace: Option<usize> - Evaluations between two points of the convergence traces, which are not recorded if None
+/// - instances: Vec<InstanceEntry> - Problem instances
+/// - algorithms: Vec<AlgorithmEntry> - Algorithms and their parameters
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub seeds: Vec<u64>,
+    #[serde(default = "default_output")]
+    pub output: String,
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+    pub trace: Option<usize>,
+    pub instances: Vec<InstanceEntry>,
+    pub algorithms: Vec<AlgorithmEntry>,
+}
+
+/// Problem instance of a manifest
+/// - scaling: Option<String> - Scaling of each feature of the data (none by default)
+/// - drop_constant: bool - Whether features with the same value in every point are removed
+/// - metric: Option<String> - Distance metric (Euclidean by default)
+/// - must_link_penalty, cannot_link_penalty: f64 - Multipliers of the weights of violated constraints (1 by default)
+/// - ground_truth: Option<String> - File with the class of each point, if it is known
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InstanceEntry {
+    pub name: String,
+    pub data: String,
+    pub constraints: String,
+    pub k: usize,
+    pub scaling: Option<String>,
+    #[serde(default)]
+    pub drop_constant: bool,
+    pub metric: Option<String>,
+    #[serde(default = "default_penalty")]
+    pub must_link_penalty: f64,
+    #[serde(default = "default_penalty")]
+    pub cannot_link_penalty: f64,
+    pub ground_truth: Option<String>,
+}
+
+impl InstanceEntry {
+    /// Preprocessing of the data of the instance
+    pub fn preprocessing(&self) -> Result<Preprocessing, String> {
+        let scaling = match self.scaling.as_deref() {
+            None => Scaling::None,
+            Some(name) => Scaling::from_name(name).ok_or_else(|| {
+                format!("{}: unknown scaling '{}' (available: {})", self.name, name, Scaling::NAMES.join(", "))
+            })?,
+        };
+
+        Ok(Preprocessing { scaling, drop_constant: self.drop_constant })
+    }
+
+    /// Multipliers of the weights of violated must-links and cannot-links
+    pub fn penalties(&self) -> (f64, f64) {
+        (self.must_link_penalty, self.cannot_link_penalty)
+    }
+
+    /// Name of the distance metric of the instance
+    pub fn metric(&self) -> &str {
+        self.metric.as_deref().unwrap_or("euclidean")
+    }
+}
+
+/// Algorithm of a manifest
+/// - algorithm: String - Name of a registered algorithm
+/// - name: Option<String> - Name of the configuration in the results (the algorithm by default)
+/// - output: Option<String> - Results directory (`<output>/<name>` by default)
+/// - budget: Option<usize> - Maximum number of fitness evaluations
+/// - params: Option<toml::Table> - Parameters that replace the default ones of the algorithm
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlgorithmEntry {
+    pub algorithm: String,
+    pub name: Option<String>,
+    pub output: Option<String>,
+    pub budget: Option<usize>,
+    pub params: Option<toml::Table>,
+}
+
+fn default_output() -> String {
+    "results".to_string()
+}
+
+fn default_workers() -> usize {
+    1
+}
+
+fn default_penalty() -> f64 {
+    1.0
+}
+
+impl Manifest {
+    /// Reads a TOML manifest
+    pub fn from_file(path: &str) -> Result<Manifest, String> {
+        let content = read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        toml::from_str(&content).map_err(|e| format!("{}: {}", path, e))
+    }
+}
+
+impl AlgorithmEntry {
+    /// Name of the configuration in the results
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.algorithm)
+    }
+
+    /// Results directory of the configuration
+    pub fn output(&self, manifest_output: &str) -> String {
+        self.output.clone().unwrap_or_else(|| format!("{}/{}", manifest_output, self.name()))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL: &str = r#"
+seeds = [1, 2]
+
+[[instances]]
+name = "zoo10"
+data = "zoo.dat"
+constraints = "zoo.const"
+k = 7
+
+[[algorithms]]
+algorithm = "es"
+"#;
+
+    #[test]
+    fn omitted_fields_take_their_defaults() {
+        let manifest: Manifest = toml::from_str(MINIMAL).unwrap();
+        assert_eq!(manifest.seeds, vec![1, 2]);
+        assert_eq!(manifest.output, "results");
+        assert_eq!(manifest.workers, 1);
+        assert_eq!(manifest.trace, None);
+
+        let instance = &manifest.instances[0];
+        assert_eq!(instance.penalties(), (1.0, 1.0));
+        assert_eq!(instance.metric(), "euclidean");
+        assert!(instance.ground_truth.is_none());
+        let preprocessing = instance.preprocessing().unwrap();
+        assert_eq!(preprocessing.scaling, Scaling::None);
+        assert!(!preprocessing.drop_constant);
+
+        let algorithm = &manifest.algorithms[0];
+        assert_eq!(algorithm.name(), "es");
+        assert_eq!(algorithm.output("out"), "out/es");
+        assert!(algorithm.budget.is_none() && algorithm.params.is_none());
+    }
+
+    #[test]
+    fn algorithms_keep_their_name_output_and_parameters() {
+        let content = format!("{}{}", MINIMAL, r#"
+[[algorithms]]
+algorithm = "am-10-0.1"
+name = "am-small"
+output = "elsewhere"
+budget = 5000
+params = { period = 5, genetic = { population_size = 10 } }
+"#);
+        let manifest: Manifest = toml::from_str(&content).unwrap();
+        let algorithm = &manifest.algorithms[1];
+
+        assert_eq!(algorithm.name(), "am-small");
+        assert_eq!(algorithm.output("out"), "elsewhere");
+        assert_eq!(algorithm.budget, Some(5000));
+        let params = algorithm.params.as_ref().unwrap();
+        assert_eq!(params["period"].as_integer(), Some(5));
+        assert_eq!(params["genetic"]["population_size"].as_integer(), Some(10));
+    }
+
+    #[test]
+    fn unknown_and_missing_fields_are_rejected() {
+        let unknown = MINIMAL.replace("k = 7", "k = 7\nclusters = 7");
+        assert!(toml::from_str::<Manifest>(&unknown).is_err());
+
+        let missing = MINIMAL.replace("seeds = [1, 2]", "");
+        assert!(toml::from_str::<Manifest>(&missing).is_err());
+    }
+
+    #[test]
+    fn unknown_scalings_are_reported_with_the_instance() {
+        let content = MINIMAL.replace("k = 7", "k = 7\nscaling = \"log\"");
+        let manifest: Manifest = toml::from_str(&content).unwrap();
+
+        let error = manifest.instances[0].preprocessing().unwrap_err();
+        assert!(error.starts_with("zoo10: unknown scaling 'log'"), "{}", error);
+    }
+
+    #[test]
+    fn unreadable_manifests_are_reported_with_their_path() {
+        let error = Manifest::from_file("missing-manifest.toml").err().unwrap();
+        assert!(error.starts_with("missing-manifest.toml: "), "{}", error);
+    }
+}
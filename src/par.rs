@@ -3,21 +3,136 @@ use serde_derive::*;
 
 // Declare sub-modules
 mod problem;
+mod metric;
+mod preprocessing;
+mod error;
 mod partition;
+mod labels;
+mod report;
+mod metrics;
+mod evaluator;
+mod result;
+mod solver;
+mod trace;
+#[cfg(test)]
+mod testing;
+/// Metaheuristics for the PAR problem
 pub mod algorithms;
+/// Synthetic instances
+pub mod generator;
 
 // Use par::<element> instead of calling par::<submodule>::<element>
-pub use problem::Problem;
+pub use problem::{Problem, Point, ConstraintFormat};
+pub use metric::Metric;
+pub use preprocessing::{Scaling, Preprocessing, Transform};
+pub use error::{ProblemError, PartitionError};
 pub use partition::Cluster;
 pub use partition::Partition;
+pub use labels::PartitionInfo;
+pub use report::{PartitionReport, ClusterReport, Violation, ConstraintKind};
+pub use metrics::{Contingency, InternalScores, ExternalScores};
+pub use evaluator::Evaluator;
+pub use result::SolveResult;
+pub use trace::{Observer, NoObserver, Trace, TracePoint, Embedded};
+pub use solver::{Solver, Configured, Algorithm, Constructor, Registry, LocalSearchParams, with_params};
 
-// Struct for serialization
+/// Row of the results CSV of an algorithm, one per execution
+/// - instance: usize - Seed of the execution
+/// - aggregate: f64 - Fitness of the final partition
+/// - infeasibility: f64 - Weighted sum of the constraints violated by the final partition
+/// - general_deviation: f64 - Mean intra-cluster distance of the final partition
+/// - time: u128 - Wall time, in milliseconds
+/// - evaluations: usize - Fitness evaluations spent
+/// - iterations: usize - Iterations of the algorithm
+/// - restarts: usize - Times the algorithm started again from scratch
+/// - accepted_worse: Option<usize> - Worse moves accepted, only written for the algorithms based on simulated annealing
+/// - silhouette, davies_bouldin, calinski_harabasz: f64 - Cluster-validity indices of the final partition
+/// - ari, nmi, purity: Option<f64> - Agreement with the ground truth, only written if it is known
 #[derive(Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ExecutionRecord {
     pub instance: usize,
     pub aggregate: f64,
-    pub infeasibility: usize,
+    pub infeasibility: f64,
     pub general_deviation: f64,
     pub time: u128,
+    pub evaluations: usize,
+    pub iterations: usize,
+    pub restarts: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_worse: Option<usize>,
+    pub silhouette: f64,
+    pub davies_bouldin: f64,
+    pub calinski_harabasz: f64,
+    #[serde(rename = "ARI", skip_serializing_if = "Option::is_none")]
+    pub ari: Option<f64>,
+    #[serde(rename = "NMI", skip_serializing_if = "Option::is_none")]
+    pub nmi: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purity: Option<f64>,
+}
+
+impl ExecutionRecord {
+    /// Record of an execution, scoring its final partition
+    /// The validity indices and the agreement with the ground truth are calculated here, once the
+    /// execution has finished, so they are not part of its time
+    /// - seed: u64 - Seed of the execution, which identifies it in the results
+    /// - problem: &Problem - Instance the execution solved
+    /// - result: &SolveResult - Result of the execution
+    pub fn new(seed: u64, problem: &Problem, result: &SolveResult) -> ExecutionRecord {
+        let internal = problem.internal_scores(&result.partition);
+        let external = problem.external_scores(&result.partition);
+
+        ExecutionRecord {
+            instance: seed as usize,
+            aggregate: result.aggregate,
+            infeasibility: result.infeasibility,
+            general_deviation: result.general_deviation,
+            time: result.time.as_millis(),
+            evaluations: result.evaluations,
+            iterations: result.iterations,
+            restarts: result.restarts,
+            accepted_worse: result.accepted_worse,
+            silhouette: internal.silhouette,
+            davies_bouldin: internal.davies_bouldin,
+            calinski_harabasz: internal.calinski_harabasz,
+            ari: external.map(|e| e.adjusted_rand_index),
+            nmi: external.map(|e| e.normalized_mutual_information),
+            purity: external.map(|e| e.purity),
+        }
+    }
+}
+
+/// Row of the convergence trace CSV of an algorithm, one per recorded state
+/// - seed: u64 - Seed of the execution
+/// - evaluations: usize - Fitness evaluations spent
+/// - time: f64 - Milliseconds since the start of the execution
+/// - fitness: f64 - Fitness of the current solution
+/// - best_fitness: f64 - Fitness of the best solution found so far
+/// - infeasibility: f64 - Infeasibility of the current solution
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TraceRecord {
+    pub seed: u64,
+    pub evaluations: usize,
+    pub time: f64,
+    pub fitness: f64,
+    pub best_fitness: f64,
+    pub infeasibility: f64,
+}
+
+impl TraceRecord {
+    /// Record of a state of the trace of an execution
+    /// - seed: u64 - Seed of the execution
+    /// - point: &TracePoint - Recorded state
+    pub fn new(seed: u64, point: &TracePoint) -> TraceRecord {
+        TraceRecord {
+            seed,
+            evaluations: point.evaluations,
+            time: point.time,
+            fitness: point.fitness,
+            best_fitness: point.best_fitness,
+            infeasibility: point.infeasibility,
+        }
+    }
 }
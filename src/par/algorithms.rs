@@ -1,34 +1,126 @@
 use super::Problem;
 use super::Partition;
+use super::Evaluator;
+use super::{SolveResult, Observer};
 use rand::seq::SliceRandom;
+use serde_derive::{Serialize, Deserialize};
 use rand_pcg::Pcg64;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+// Declare sub-modules
+mod genetic;
+mod memetic;
+mod annealing;
+mod multistart;
+mod iterated;
+mod initialization;
+
+// Use algorithms::<element> instead of calling algorithms::<submodule>::<element>
+pub use genetic::{generational_genetic, steady_state_genetic, Crossover, GeneticParams};
+pub use memetic::{memetic, MemeticParams};
+pub use annealing::{simulated_annealing, AnnealingParams};
+pub use multistart::{multi_start_local_search, MultiStartParams};
+pub use iterated::{iterated_local_search, iterated_annealing, IteratedParams};
+pub use initialization::Initialization;
+
+// Differences below this value are rounding errors, not improvements: fitness decreases of the incremental
+// evaluation, or differences between the weighted infeasibility increments of the greedy COPKM
+const IMPROVEMENT_TOLERANCE: f64 = 1e-12;
+
+/// Checks that a parameter is a probability, in [0, 1]
+/// - name: &str - Name of the parameter, for the error message
+/// - value: f64 - Value of the parameter
+fn check_probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    }
+    else {
+        Err(format!("{} must be between 0 and 1, not {}", name, value))
+    }
+}
+
+
+/// Parameters of the greedy COPKM
+/// - initialization: Initialization - Strategy to place the initial centroids
+/// - max_restarts: usize - Times the algorithm is started again from new centroids when it ends
+///   with an empty cluster, before the empty clusters are filled with their nearest elements
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GreedyParams {
+    pub initialization: Initialization,
+    pub max_restarts: usize,
+}
+
+impl Default for GreedyParams {
+    fn default() -> Self {
+        GreedyParams {
+            initialization: Initialization::Uniform,
+            max_restarts: 100,
+        }
+    }
+}
 
 
 /// Greedy COPKM
-/// - problem: &mut par::Problem - Instance of a problem
+/// Restarts from new centroids while the final partition has an empty cluster, up to
+/// `max_restarts` times
+/// - problem: &par::Problem - Instance of a problem
+/// - params: &GreedyParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution, which only sees the final partition
 /// - rng: &mut rand_pcg::Pcg64 - Random number generator
 /// #### Return value
-/// (Partition, usize, f64) Final partition, infeasibility and general deviation
-pub fn greedy(problem: &Problem, rng: &mut Pcg64) -> (Partition, f64, usize, f64) {
-    // Step 1: create k empty clusters with a random centroid
-    let dimension = problem.data(0).len();
-    let mut partition = Partition::new(problem.k(), dimension, rng);
+/// SolveResult Final partition and its scores; each pass over the elements is an iteration, and
+/// the restarts are counted in `restarts`
+pub fn greedy(problem: &Problem, params: &GreedyParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    let start = Instant::now();
+    let mut iterations = 0;
+    let mut restarts = 0;
+
+    let mut partition = loop {
+        let (partition, passes) = copkm(problem, params.initialization, rng);
+        iterations += passes;
+
+        // If the partition is invalid, start again
+        if partition.clusters().iter().all(|c| !c.is_empty()) || restarts == params.max_restarts {
+            break partition;
+        }
+        restarts += 1;
+    };
+    fill_empty_clusters(problem, &mut partition);
+
+    // Calculate the aggregate of the partition and return it with its associated values
+    let partition_aggr = problem.fitness(&partition);
+    let mut result = SolveResult::new(problem, partition, partition_aggr, 1, iterations, start.elapsed());
+    result.restarts = restarts;
+    observer.observe(1, partition_aggr, partition_aggr, &|| result.infeasibility);
+    result
+}
+
+/// One execution of the greedy COPKM, which may end with empty clusters
+/// #### Return value
+/// (Partition, usize) Final partition and number of passes over the elements
+fn copkm(problem: &Problem, initialization: Initialization, rng: &mut Pcg64) -> (Partition, usize) {
+    // Step 1: create k empty clusters with their initial centroids
+    let mut partition = initialization.partition(problem, rng);
 
     // Step 2: Shuffle element indexes
-    let mut data = problem.get_data();
-    data.shuffle(rng);
+    let mut indexes: Vec<usize> = (0..problem.size()).collect();
+    indexes.shuffle(rng);
 
     // Step 3: while there are changes in clustering
     let mut changes = true;
+    let mut iterations = 0;
     while changes {
         changes = false;
+        iterations += 1;
         
         // Step 4: for every element
-        for (element_index, element) in data.iter().enumerate() {
+        for &element_index in indexes.iter() {
+            let element = problem.data(element_index);
             // Calculate infeasibility increment of assigning to each cluster
-            let mut cluster_infeasibility = HashMap::new();
-            let mut min_infeasibility = usize::MAX;
+            let mut cluster_infeasibility = BTreeMap::new();
+            let mut min_infeasibility = f64::INFINITY;
 
             for cluster in 0..problem.k() {
                 let infeasibility = problem.inf_insert(element_index, cluster, partition.cluster_index());
@@ -41,14 +133,15 @@ pub fn greedy(problem: &Problem, rng: &mut Pcg64) -> (Partition, f64, usize, f64
             }
 
             // Of the clusters with lesser infeasibility increment, select the nearest and insert the element
+            // Weighted increments are sums of floats, so ties are compared within a tolerance
+            let tolerance = IMPROVEMENT_TOLERANCE * min_infeasibility.abs().max(1.0);
             let mut candidates: Vec<usize> = cluster_infeasibility.iter()
-                .filter(|x| *x.1 == min_infeasibility).map(|(index, _)| *index)
+                .filter(|x| *x.1 - min_infeasibility <= tolerance).map(|(index, _)| *index)
                 .collect();
             
             candidates.sort_by(|a, b| {
-                element.metric_distance(partition.get_cluster(*a).centroid())
-                    .partial_cmp(&element.metric_distance(partition.get_cluster(*b).centroid()))
-                    .unwrap()
+                problem.distance(element, partition.get_cluster(*a).centroid())
+                    .total_cmp(&problem.distance(element, partition.get_cluster(*b).centroid()))
             });
             let best = candidates[0];
             
@@ -66,10 +159,10 @@ pub fn greedy(problem: &Problem, rng: &mut Pcg64) -> (Partition, f64, usize, f64
             // Calculate new centroid with the assigned elements
             let centroid = problem.calc_centroid(partition.get_cluster(c));
 
-            // If the cluster is currently empty, set a new random centroid
+            // If the cluster is currently empty, set a new centroid
             // Else, calculate the new one
             if centroid.get(0).unwrap().is_nan() {
-                partition.get_cluster_mut(c).randomize_centroid(rng);
+                initialization.reseed(problem, &mut partition, c, rng);
             }
             else {
                 partition.get_cluster_mut(c).set_centroid(centroid);
@@ -77,81 +170,188 @@ pub fn greedy(problem: &Problem, rng: &mut Pcg64) -> (Partition, f64, usize, f64
         }
     }
 
-    // If the given partition is invalid, relaunch recursively
-    if partition.clusters().iter().find(|c| c.is_empty()).is_some() {
-        greedy(problem, rng)
+    (partition, iterations)
+}
+
+/// Moves into each empty cluster the element nearest to its centroid, among the elements that can
+/// leave their cluster
+fn fill_empty_clusters(problem: &Problem, partition: &mut Partition) {
+    for cluster in 0..problem.k() {
+        if !partition.get_cluster(cluster).is_empty() {
+            continue;
+        }
+
+        let centroid = partition.get_cluster(cluster).centroid().clone();
+        let nearest = (0..problem.size())
+            .filter(|&e| partition.can_move(e))
+            .min_by(|&a, &b| problem.distance(problem.data(a), &centroid).total_cmp(&problem.distance(problem.data(b), &centroid)));
+
+        if let Some(element) = nearest {
+            partition.insert_and_update(element, cluster, problem);
+        }
     }
-    else {
-        // Calculate the aggregate, infeasibility and general deviation of the partition
-        let partition_aggr = problem.fitness(&partition);
-        let partition_inf = problem.calc_infeasiblity(partition.cluster_index());
-        let partition_dev = problem.general_deviation(partition.clusters());
+}
 
-        // Return partition and associated values
-        (partition.clone(), partition_aggr, partition_inf, partition_dev)
+/// Neighbourhood operator: every move of an element to a cluster other than its current one
+/// - problem: &Problem - Instance of a problem
+/// - labels: &[usize] - Cluster of each element of the partition whose neighbourhood is generated
+/// #### Return value
+/// Vec<(usize, usize)> (element, new cluster) pairs, sorted by element
+pub fn neighbourhood(problem: &Problem, labels: &[usize]) -> Vec<(usize, usize)> {
+    let mut neighbourhood: Vec<(usize, usize)> = Vec::new();
+    for (element, current_cluster) in labels.iter().enumerate() {
+        for cluster in (0..problem.k()).filter(|x| x != current_cluster) { 
+            neighbourhood.push((element, cluster));
+        }
     }
+
+    neighbourhood
 }
 
 /// Local search algorithm
+/// First-improvement descent over the neighbourhood, until no neighbour improves the current
+/// partition or the evaluation budget is spent
 /// - problem: &Problem - Instance of a problem
+/// - initial: Partition - Initial partition
+/// - max_evaluations: usize - Maximum number of fitness evaluations, including the initial one
+/// - observer: &mut dyn Observer - Observer of the execution
 /// - rng: &Pcg64 - Random number generator
 /// #### Return value
-/// I don't know
-pub fn local_search(problem: &Problem, rng: &mut Pcg64) -> (Partition, f64, usize, f64) {
-    // Neighbourhood operator
-    let gen_neighbourhood = |partition: &Partition| -> Vec<(usize, usize)> {
-        let mut neighbourhood: Vec<(usize, usize)> = Vec::new();
-        for (element, current_cluster) in partition.cluster_index() {
-            for cluster in (0..problem.k()).filter(|x| x != current_cluster) { 
-                neighbourhood.push((*element, cluster));
-            }
-        }
+/// SolveResult Final partition and its scores; each applied move is an iteration
+pub fn local_search(problem: &Problem, initial: Partition, max_evaluations: usize, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    let start = Instant::now();
+    let (final_partition, current_fitness, evaluations, moves) = descent(problem, initial, max_evaluations, observer, rng);
 
-        neighbourhood
-    };
-
-    // Algorithm
+    SolveResult::new(problem, final_partition, current_fitness, evaluations, moves, start.elapsed())
+}
 
-    // Start with a greedy
-    let (first_partition, _, _, _) = greedy(problem, rng);
-    let mut current_fitness = problem.fitness(&first_partition);
-    
-    // Some(Partition) if a best neighbour is found, None if not
-    let mut final_partition = first_partition.clone();
-    let mut current_partition = Some(first_partition);
+/// Local search descent shared by the algorithms that embed a local search
+/// Neighbours are evaluated incrementally, each one counting as a fitness evaluation
+/// #### Return value
+/// (Partition, f64, usize, usize) Final partition, its fitness, the number of evaluations spent and
+/// the number of moves applied
+pub(super) fn descent(problem: &Problem, initial: Partition, max_evaluations: usize, observer: &mut dyn Observer, rng: &mut Pcg64) -> (Partition, f64, usize, usize) {
+    let mut current = Evaluator::new(problem, &initial);
+    let mut fitness = current.fitness(problem);
+    let mut evaluations = 1;
+    let mut moves = 0;
+    observer.observe(evaluations, fitness, fitness, &|| current.infeasibility());
 
-    // Loop ends if no better neighbour has been found
-    while current_partition.is_some() {
-        // Take the value and leave None on its place
-        let current = current_partition.take().unwrap();
+    // Loop ends if no better neighbour has been found or the budget is spent
+    let mut improved = true;
+    while improved {
+        improved = false;
 
         // Generate neighbourhood and shuffle it
-        let mut neighbourhood = gen_neighbourhood(&current);
+        let mut neighbourhood = neighbourhood(problem, current.labels());
         neighbourhood.shuffle(rng);
 
         for (element, new_cluster) in neighbourhood {
-            // Generate neighbour, and if valid, calculate fitness and compare with current partition
-            let neighbour = current.gen_neighbour(element, new_cluster, problem);
-
-            if let Some(valid) = neighbour {
-                let valid_fitness = problem.fitness(&valid);
-                if valid_fitness < current_fitness {
-                    current_partition = Some(valid);
-                    current_fitness = valid_fitness;
+            if evaluations >= max_evaluations {
+                break;
+            }
+
+            // If the neighbour is valid, apply the move when it improves the current partition
+            if let Some(delta) = current.move_delta(problem, element, new_cluster) {
+                evaluations += 1;
+
+                if delta < -IMPROVEMENT_TOLERANCE {
+                    current.apply_move(problem, element, new_cluster);
+                    fitness = current.fitness(problem);
+                    moves += 1;
+                    improved = true;
+                }
+
+                observer.observe(evaluations, fitness, fitness, &|| current.infeasibility());
+                if improved {
                     break;
                 }
             }
         }
+    }
+
+    (current.to_partition(problem), fitness, evaluations, moves)
+}
+
+/// Soft local search (BLS)
+/// Elements are visited once in random order, moving each one to its best cluster if that improves
+/// the partition. The search ends when every element has been visited, `max_failures` elements
+/// could not be improved or the evaluation budget has been spent
+/// - problem: &Problem - Instance of a problem
+/// - partition: &Partition - Initial partition
+/// - max_failures: usize - Maximum number of elements without improvement
+/// - max_evaluations: usize - Maximum number of fitness evaluations
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// (Partition, f64, usize) Final partition, its fitness and the number of evaluations spent
+pub fn soft_local_search(problem: &Problem, partition: &Partition, max_failures: usize, max_evaluations: usize, rng: &mut Pcg64) -> (Partition, f64, usize) {
+    let mut current = Evaluator::new(problem, partition);
+    let mut evaluations = 0;
+    let mut failures = 0;
+
+    // Group the moves of the neighbourhood by element, and visit the elements in random order
+    let mut moves: Vec<Vec<(usize, usize)>> = Vec::new();
+    for (element, cluster) in neighbourhood(problem, current.labels()) {
+        match moves.last_mut() {
+            Some(group) if group[0].0 == element => group.push((element, cluster)),
+            _ => moves.push(vec![(element, cluster)]),
+        }
+    }
+    moves.shuffle(rng);
+
+    for group in moves {
+        if failures >= max_failures || evaluations >= max_evaluations {
+            break;
+        }
+
+        // Best move of the element
+        let mut best: Option<(usize, usize, f64)> = None;
+        for (element, new_cluster) in group {
+            if evaluations >= max_evaluations {
+                break;
+            }
+
+            if let Some(delta) = current.move_delta(problem, element, new_cluster) {
+                evaluations += 1;
+
+                if best.is_none_or(|(_, _, d)| delta < d) {
+                    best = Some((element, new_cluster, delta));
+                }
+            }
+        }
 
-        // If current_partition is None, save current in final_partition
-        if current_partition.is_none() {
-            final_partition = current;
+        match best {
+            Some((element, new_cluster, delta)) if delta < -IMPROVEMENT_TOLERANCE => {
+                current.apply_move(problem, element, new_cluster);
+            },
+            _ => failures += 1,
         }
     }
 
-    // Calculate data of the final partition
-    let inf = problem.calc_infeasiblity(final_partition.cluster_index());
-    let deviation = problem.general_deviation(final_partition.clusters());
+    (current.to_partition(problem), current.fitness(problem), evaluations)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::{Point, NoObserver};
+    use rand::SeedableRng;
+
+    #[test]
+    fn greedy_restarts_a_bounded_number_of_times_and_fills_the_empty_clusters() {
+        // Every element must be with every other one, so the COPKM always ends with a single cluster
+        let points = (0..6).map(|i| Point::from_vec(vec![i as f64])).collect();
+        let constraints: Vec<(usize, usize, f64)> = (0..6).flat_map(|a| (a + 1..6).map(move |b| (a, b, 1.0))).collect();
+        let problem = Problem::from_points(points, &constraints, 3);
 
-    (final_partition, current_fitness, inf, deviation)
-}
\ No newline at end of file
+        for initialization in [Initialization::Uniform, Initialization::KMeansPlusPlus] {
+            let params = GreedyParams { initialization, max_restarts: 4 };
+            let result = greedy(&problem, &params, &mut NoObserver, &mut Pcg64::seed_from_u64(1));
+
+            assert_eq!(result.restarts, 4);
+            assert!(result.partition.clusters().iter().all(|c| !c.is_empty()));
+            assert_eq!(result.partition.labels().len(), 6);
+        }
+    }
+}
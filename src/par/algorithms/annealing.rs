@@ -0,0 +1,242 @@
+use crate::par::{Problem, Partition, Evaluator, SolveResult, Observer, Embedded};
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::time::Instant;
+use serde_derive::{Serialize, Deserialize};
+
+
+/// Parameters of the simulated annealing
+/// - initial_temperature: Option<f64> - Initial temperature. If `None`, it is calculated as
+///   `mu * C(S0) / -ln(phi)`, where `C(S0)` is the fitness of the initial solution
+/// - final_temperature: f64 - Final temperature, lowered if it is not below the initial one
+/// - mu: f64 - Probability of accepting a solution `mu` times worse than the initial one...
+/// - phi: f64 - ...with probability `phi` at the initial temperature
+/// - max_neighbours: Option<usize> - Neighbours generated per temperature level (10n if `None`)
+/// - max_successes: Option<usize> - Accepted neighbours per temperature level (0.1 max_neighbours if `None`)
+/// - max_evaluations: usize - Number of fitness evaluations before stopping
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnnealingParams {
+    pub initial_temperature: Option<f64>,
+    pub final_temperature: f64,
+    pub mu: f64,
+    pub phi: f64,
+    pub max_neighbours: Option<usize>,
+    pub max_successes: Option<usize>,
+    pub max_evaluations: usize,
+}
+
+impl Default for AnnealingParams {
+    fn default() -> Self {
+        AnnealingParams {
+            initial_temperature: None,
+            final_temperature: 1e-3,
+            mu: 0.3,
+            phi: 0.3,
+            max_neighbours: None,
+            max_successes: None,
+            max_evaluations: 100_000,
+        }
+    }
+}
+
+impl AnnealingParams {
+    /// Checks that the cooling schedule is well defined: the temperatures must be positive and
+    /// `phi` a probability strictly between 0 and 1, so that the initial temperature is finite
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(temperature) = self.initial_temperature {
+            if !(temperature > 0.0 && temperature.is_finite()) {
+                return Err(format!("the initial temperature must be positive, not {}", temperature));
+            }
+        }
+        if !(self.final_temperature > 0.0 && self.final_temperature.is_finite()) {
+            return Err(format!("the final temperature must be positive, not {}", self.final_temperature));
+        }
+        if !(self.mu > 0.0 && self.mu.is_finite()) {
+            return Err(format!("mu must be positive, not {}", self.mu));
+        }
+        if !(self.phi > 0.0 && self.phi < 1.0) {
+            return Err(format!("phi must be between 0 and 1 (both excluded), not {}", self.phi));
+        }
+        Ok(())
+    }
+}
+
+
+/// Simulated annealing with a Cauchy-modified cooling schedule, starting from a random partition
+/// - problem: &Problem - Instance of a problem
+/// - params: &AnnealingParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores, each cooling level being an iteration, with the
+/// number of accepted worse moves
+/// Panics if the parameters are not valid (see `AnnealingParams::validate`)
+pub fn simulated_annealing(problem: &Problem, params: &AnnealingParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+    let start = Instant::now();
+    let initial = Partition::new_rand(problem, rng);
+    let fitness = problem.fitness(&initial);
+    observer.observe(1, fitness, fitness, &|| problem.calc_infeasiblity(initial.cluster_index()));
+
+    let mut embedded = Embedded::new(observer, 1, fitness);
+    let (best, best_fitness, evaluations, levels, accepted_worse) = anneal(problem, initial, fitness, params, &mut embedded, rng);
+
+    let mut result = SolveResult::new(problem, best, best_fitness, evaluations + 1, levels, start.elapsed());
+    result.accepted_worse = Some(accepted_worse);
+    result
+}
+
+/// Simulated annealing from a given partition
+/// The evaluation of the initial partition is not counted in the budget
+/// #### Return value
+/// (Partition, f64, usize, usize, usize) Best partition, its fitness, evaluations spent, cooling levels
+/// and accepted worse moves
+pub(super) fn anneal(problem: &Problem, initial: Partition, fitness: f64, params: &AnnealingParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> (Partition, f64, usize, usize, usize) {
+    let n = problem.size();
+    let k = problem.k();
+
+    // Cooling schedule
+    let max_neighbours = params.max_neighbours.unwrap_or(10 * n).max(1);
+    let max_successes = params.max_successes.unwrap_or(max_neighbours / 10).max(1);
+    let coolings = (params.max_evaluations / max_neighbours).max(1) as f64;
+
+    let initial_temperature = params.initial_temperature.unwrap_or(params.mu * fitness / -params.phi.ln());
+    let final_temperature = if params.final_temperature < initial_temperature {
+        params.final_temperature
+    }
+    else {
+        initial_temperature * 1e-3
+    };
+    let beta = (initial_temperature - final_temperature) / (coolings * initial_temperature * final_temperature);
+
+    // Algorithm, evaluating every neighbour incrementally
+    let mut current = Evaluator::new(problem, &initial);
+    let mut current_fitness = fitness;
+    let mut best = current.labels().to_vec();
+    let mut best_fitness = current_fitness;
+
+    let mut temperature = initial_temperature;
+    let mut evaluations = 0;
+    let mut accepted_worse = 0;
+    let mut levels = 0;
+
+    // Loop ends when the budget is spent, no neighbour has been accepted in a whole level or the
+    // final temperature has been reached
+    let mut successes = 1;
+    while evaluations < params.max_evaluations && successes > 0 && temperature > final_temperature && k > 1 {
+        successes = 0;
+        levels += 1;
+        let mut neighbours = 0;
+
+        while neighbours < max_neighbours && successes < max_successes && evaluations < params.max_evaluations {
+            // Random move of a random element to a different cluster
+            let element = rng.gen_range(0..n);
+            let new_cluster = (current.labels()[element] + rng.gen_range(1..k)) % k;
+            neighbours += 1;
+
+            if let Some(delta) = current.move_delta(problem, element, new_cluster) {
+                evaluations += 1;
+
+                // Metropolis criterion
+                if delta < 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+                    if delta > 0.0 {
+                        accepted_worse += 1;
+                    }
+
+                    current.apply_move(problem, element, new_cluster);
+                    current_fitness = current.fitness(problem);
+                    successes += 1;
+
+                    if current_fitness < best_fitness {
+                        best = current.labels().to_vec();
+                        best_fitness = current_fitness;
+                    }
+                }
+
+                observer.observe(evaluations, current_fitness, best_fitness, &|| current.infeasibility());
+            }
+        }
+
+        // Cauchy-modified cooling
+        temperature /= 1.0 + beta * temperature;
+    }
+
+    (Partition::from_labels(&best, problem), best_fitness, evaluations, levels, accepted_worse)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::Point;
+    use rand::SeedableRng;
+
+    /// Observer that counts the evaluations after which the current fitness got worse
+    struct Worsenings {
+        last: Option<(usize, f64)>,
+        count: usize,
+    }
+
+    impl Observer for Worsenings {
+        fn observe(&mut self, evaluations: usize, fitness: f64, _: f64, _: &dyn Fn() -> f64) {
+            if self.last.is_some_and(|(_, last)| fitness > last) {
+                self.count += 1;
+            }
+            self.last = Some((evaluations, fitness));
+        }
+    }
+
+    /// Problem with random coordinates, so that no move leaves the fitness unchanged
+    fn problem() -> Problem {
+        let mut rng = Pcg64::seed_from_u64(0);
+        let points = (0..20).map(|_| Point::from_fn(2, |_, _| rng.gen_range(0.0..10.0))).collect();
+        Problem::from_points(points, &[(0, 1, 1.0), (2, 7, -1.0), (10, 15, 1.0)], 3)
+    }
+
+    /// Anneals from a random partition, returning the result and what the observer saw
+    fn run(params: &AnnealingParams, seed: u64) -> ((Partition, f64, usize, usize, usize), Worsenings) {
+        let problem = problem();
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let initial = Partition::new_rand(&problem, &mut rng);
+        let fitness = problem.fitness(&initial);
+
+        let mut observer = Worsenings { last: Some((0, fitness)), count: 0 };
+        let result = anneal(&problem, initial, fitness, params, &mut observer, &mut rng);
+        (result, observer)
+    }
+
+    #[test]
+    fn anneal_counts_every_accepted_worse_move() {
+        let params = AnnealingParams { initial_temperature: Some(100.0), max_evaluations: 500, ..AnnealingParams::default() };
+        for seed in 0..5 {
+            let ((_, _, _, _, accepted_worse), observer) = run(&params, seed);
+            assert!(accepted_worse > 0);
+            assert_eq!(accepted_worse, observer.count);
+        }
+    }
+
+    #[test]
+    fn anneal_accepts_no_worse_move_when_frozen() {
+        let params = AnnealingParams { initial_temperature: Some(1e-300), max_evaluations: 500, ..AnnealingParams::default() };
+        let ((_, _, _, _, accepted_worse), observer) = run(&params, 1);
+        assert_eq!(accepted_worse, 0);
+        assert_eq!(observer.count, 0);
+    }
+
+    #[test]
+    fn anneal_spends_exactly_its_budget() {
+        let params = AnnealingParams {
+            initial_temperature: Some(100.0),
+            max_successes: Some(1000),
+            max_neighbours: Some(1000),
+            max_evaluations: 137,
+            ..AnnealingParams::default()
+        };
+        let ((partition, fitness, evaluations, _, _), observer) = run(&params, 2);
+
+        assert_eq!(evaluations, 137);
+        assert_eq!(observer.last.map(|(e, _)| e), Some(137));
+        assert!((problem().fitness(&partition) - fitness).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,422 @@
+use crate::par::{Problem, Partition, SolveResult, Observer};
+use super::check_probability;
+use rand::Rng;
+use rand::seq::index::sample;
+use rand_pcg::Pcg64;
+use std::time::Instant;
+use serde_derive::{Serialize, Deserialize};
+
+
+/// Crossover operator used to combine two parents
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Crossover {
+    /// Half of the genes, chosen at random, come from each parent
+    Uniform,
+    /// A random segment is copied from the first parent, the remaining genes are crossed uniformly
+    FixedSegment,
+}
+
+/// Parameters of the genetic algorithms
+/// - population_size: usize - Number of chromosomes in the population
+/// - crossover: Crossover - Crossover operator
+/// - crossover_probability: f64 - Probability of crossing a pair of parents
+/// - mutation_probability: f64 - Probability of mutating each gene
+/// - max_evaluations: usize - Number of fitness evaluations before stopping
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneticParams {
+    pub population_size: usize,
+    pub crossover: Crossover,
+    pub crossover_probability: f64,
+    pub mutation_probability: f64,
+    pub max_evaluations: usize,
+}
+
+impl Default for GeneticParams {
+    fn default() -> Self {
+        GeneticParams {
+            population_size: 50,
+            crossover: Crossover::Uniform,
+            crossover_probability: 0.7,
+            mutation_probability: 0.001,
+            max_evaluations: 100_000,
+        }
+    }
+}
+
+impl GeneticParams {
+    /// Checks that the population can evolve: it needs at least two chromosomes to be crossed, and
+    /// the crossover or the mutation must be able to change them
+    pub fn validate(&self) -> Result<(), String> {
+        if self.population_size < 2 {
+            return Err(format!("the population needs at least 2 chromosomes, not {}", self.population_size));
+        }
+        check_probability("the crossover probability", self.crossover_probability)?;
+        check_probability("the mutation probability", self.mutation_probability)?;
+        if self.crossover_probability == 0.0 && self.mutation_probability == 0.0 {
+            return Err("the crossover and mutation probabilities cannot both be 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+
+/// Individual of a population: a label vector and its fitness
+/// - genes: Vec<usize> - Cluster assigned to each element
+/// - fitness: Option<f64> - `None` if the chromosome has changed since its last evaluation
+#[derive(Clone)]
+pub(super) struct Chromosome {
+    pub genes: Vec<usize>,
+    pub fitness: Option<f64>,
+}
+
+impl Chromosome {
+    /// Creates a random valid chromosome
+    pub fn new_rand(problem: &Problem, rng: &mut Pcg64) -> Chromosome {
+        let mut genes: Vec<usize> = (0..problem.size()).map(|_| rng.gen_range(0..problem.k())).collect();
+        repair(&mut genes, problem, rng);
+
+        Chromosome {
+            genes,
+            fitness: None,
+        }
+    }
+
+    /// Evaluates the chromosome if needed
+    /// #### Return value
+    /// `true` if a fitness evaluation has been spent
+    pub fn evaluate(&mut self, problem: &Problem) -> bool {
+        if self.fitness.is_none() {
+            self.fitness = Some(problem.fitness(&self.to_partition(problem)));
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    /// Returns the fitness of an evaluated chromosome
+    pub fn fitness(&self) -> f64 {
+        self.fitness.expect("Chromosome has not been evaluated")
+    }
+
+    /// Builds the partition represented by the chromosome
+    pub fn to_partition(&self, problem: &Problem) -> Partition {
+        Partition::from_labels(&self.genes, problem)
+    }
+}
+
+
+/// Repairs a label vector so that no cluster is left empty
+/// Every empty cluster receives a random element, as long as moving it is a valid neighbour
+/// of the partition (see `Partition::gen_neighbour`). While a cluster is empty, another one has
+/// at least two elements as long as there are at least as many elements as clusters, which
+/// `Problem::load` guarantees, so a random element can always be moved eventually
+pub(super) fn repair(genes: &mut Vec<usize>, problem: &Problem, rng: &mut Pcg64) {
+    assert!(problem.k() <= genes.len(), "Cannot make {} clusters out of {} elements", problem.k(), genes.len());
+
+    let mut partition = Partition::from_labels(genes, problem);
+    let empty: Vec<usize> = (0..problem.k()).filter(|&c| partition.get_cluster(c).is_empty()).collect();
+
+    if empty.is_empty() {
+        return;
+    }
+
+    for cluster in empty {
+        loop {
+            let element = rng.gen_range(0..genes.len());
+            if let Some(neighbour) = partition.gen_neighbour(element, cluster, problem) {
+                partition = neighbour;
+                break;
+            }
+        }
+    }
+
+    *genes = partition.labels();
+}
+
+/// Binary tournament: returns the index of the best of two random chromosomes
+pub(super) fn binary_tournament(population: &[Chromosome], rng: &mut Pcg64) -> usize {
+    let a = rng.gen_range(0..population.len());
+    let b = rng.gen_range(0..population.len());
+
+    if population[a].fitness() <= population[b].fitness() { a } else { b }
+}
+
+/// Crosses two parents and returns a new, unevaluated and repaired child
+pub(super) fn cross(first: &Chromosome, second: &Chromosome, operator: Crossover, problem: &Problem, rng: &mut Pcg64) -> Chromosome {
+    let n = first.genes.len();
+    let mut genes = second.genes.clone();
+
+    match operator {
+        Crossover::Uniform => {
+            // Take n/2 random genes from the first parent
+            for i in sample(rng, n, n / 2) {
+                genes[i] = first.genes[i];
+            }
+        },
+        Crossover::FixedSegment => {
+            // Copy a segment of random start and length from the first parent
+            let start = rng.gen_range(0..n);
+            let length = rng.gen_range(0..n);
+
+            for (i, gene) in genes.iter_mut().enumerate() {
+                let in_segment = (i + n - start) % n < length;
+                if in_segment || rng.gen_bool(0.5) {
+                    *gene = first.genes[i];
+                }
+            }
+        },
+    }
+
+    repair(&mut genes, problem, rng);
+
+    Chromosome {
+        genes,
+        fitness: None,
+    }
+}
+
+/// Mutates every gene of a chromosome with the given probability, assigning it to a different cluster
+pub(super) fn mutate(chromosome: &mut Chromosome, probability: f64, problem: &Problem, rng: &mut Pcg64) {
+    let k = problem.k();
+    if k < 2 {
+        return;
+    }
+
+    let mut mutated = false;
+
+    for i in 0..chromosome.genes.len() {
+        if rng.gen_bool(probability) {
+            let new_cluster = (chromosome.genes[i] + rng.gen_range(1..k)) % k;
+            chromosome.genes[i] = new_cluster;
+            mutated = true;
+        }
+    }
+
+    if mutated {
+        repair(&mut chromosome.genes, problem, rng);
+        chromosome.fitness = None;
+    }
+}
+
+/// Returns the index of the best chromosome of a population
+pub(super) fn best_index(population: &[Chromosome]) -> usize {
+    (0..population.len())
+        .min_by(|&a, &b| population[a].fitness().partial_cmp(&population[b].fitness()).unwrap())
+        .unwrap()
+}
+
+/// Returns the index of the worst chromosome of a population
+pub(super) fn worst_index(population: &[Chromosome]) -> usize {
+    (0..population.len())
+        .max_by(|&a, &b| population[a].fitness().partial_cmp(&population[b].fitness()).unwrap())
+        .unwrap()
+}
+
+/// Shows the best chromosome of a population to an observer
+pub(super) fn observe(problem: &Problem, population: &[Chromosome], evaluations: usize, observer: &mut dyn Observer) {
+    let best = &population[best_index(population)];
+    let infeasibility = || problem.calc_infeasiblity(best.to_partition(problem).cluster_index());
+    observer.observe(evaluations, best.fitness(), best.fitness(), &infeasibility);
+}
+
+/// Builds the result of an algorithm from the best chromosome of a population
+pub(super) fn result(problem: &Problem, population: &[Chromosome], evaluations: usize, iterations: usize, start: Instant) -> SolveResult {
+    let best = &population[best_index(population)];
+    SolveResult::new(problem, best.to_partition(problem), best.fitness(), evaluations, iterations, start.elapsed())
+}
+
+
+/// Generational genetic algorithm (AGG) with elitism
+/// - problem: &Problem - Instance of a problem
+/// - params: &GeneticParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution, which sees the best chromosome of each generation
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores; each generation is an iteration
+/// Panics if the parameters are not valid (see `GeneticParams::validate`)
+pub fn generational_genetic(problem: &Problem, params: &GeneticParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+    generational(problem, params, observer, rng, |_, _, _, _| 0)
+}
+
+/// Generational scheme shared by the AGG and the memetic algorithms
+/// - improve: FnMut(&mut [Chromosome], usize, usize, &mut Pcg64) -> usize - Called after each generation
+///   with the population, the generation number and the remaining evaluations; returns the evaluations spent
+pub(super) fn generational<F>(problem: &Problem, params: &GeneticParams, observer: &mut dyn Observer, rng: &mut Pcg64, mut improve: F) -> SolveResult
+where
+    F: FnMut(&mut [Chromosome], usize, usize, &mut Pcg64) -> usize,
+{
+    let start = Instant::now();
+
+    // Initial population
+    let mut population: Vec<Chromosome> = (0..params.population_size).map(|_| Chromosome::new_rand(problem, rng)).collect();
+    let mut evaluations = 0;
+    for chromosome in population.iter_mut() {
+        chromosome.evaluate(problem);
+        evaluations += 1;
+    }
+    observe(problem, &population, evaluations, observer);
+
+    // Expected number of crossed pairs
+    let crosses = (params.crossover_probability * (params.population_size / 2) as f64) as usize;
+    let mut generation = 0;
+
+    while evaluations < params.max_evaluations {
+        let elite = population[best_index(&population)].clone();
+
+        // Selection: as many binary tournaments as chromosomes in the population
+        let mut offspring: Vec<Chromosome> = (0..params.population_size)
+            .map(|_| population[binary_tournament(&population, rng)].clone())
+            .collect();
+
+        // Crossover: the first pairs are crossed, the rest are copied
+        for pair in 0..crosses {
+            let (a, b) = (2 * pair, 2 * pair + 1);
+            let first = cross(&offspring[a], &offspring[b], params.crossover, problem, rng);
+            let second = cross(&offspring[b], &offspring[a], params.crossover, problem, rng);
+            offspring[a] = first;
+            offspring[b] = second;
+        }
+
+        // Mutation
+        for chromosome in offspring.iter_mut() {
+            mutate(chromosome, params.mutation_probability, problem, rng);
+        }
+
+        // Evaluation of the changed chromosomes, never exceeding the evaluation budget; the children
+        // that cannot be evaluated are dropped, which only happens in the last generation
+        for chromosome in offspring.iter_mut() {
+            if chromosome.fitness.is_none() && evaluations < params.max_evaluations {
+                chromosome.evaluate(problem);
+                evaluations += 1;
+            }
+        }
+        offspring.retain(|c| c.fitness.is_some());
+
+        // Elitism: if the best parent has not survived, replace the worst child, or take the place
+        // of a dropped one
+        if !offspring.iter().any(|c| c.genes == elite.genes) {
+            if offspring.len() < params.population_size {
+                offspring.push(elite);
+            }
+            else {
+                let worst = worst_index(&offspring);
+                offspring[worst] = elite;
+            }
+        }
+
+        population = offspring;
+        generation += 1;
+
+        // Improvement of the new population
+        let remaining = params.max_evaluations.saturating_sub(evaluations);
+        evaluations += improve(&mut population, generation, remaining, rng);
+        observe(problem, &population, evaluations, observer);
+    }
+
+    result(problem, &population, evaluations, generation, start)
+}
+
+/// Steady-state genetic algorithm (AGE)
+/// Each iteration two parents are selected by binary tournament and their two children compete
+/// with the two worst chromosomes of the population to take their place
+/// - problem: &Problem - Instance of a problem
+/// - params: &GeneticParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution, which sees the best chromosome of each iteration
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores; each replacement step is an iteration
+/// Panics if the parameters are not valid (see `GeneticParams::validate`)
+pub fn steady_state_genetic(problem: &Problem, params: &GeneticParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+    let start = Instant::now();
+
+    // Initial population
+    let mut population: Vec<Chromosome> = (0..params.population_size).map(|_| Chromosome::new_rand(problem, rng)).collect();
+    let mut evaluations = 0;
+    for chromosome in population.iter_mut() {
+        chromosome.evaluate(problem);
+        evaluations += 1;
+    }
+    observe(problem, &population, evaluations, observer);
+
+    let mut iterations = 0;
+    while evaluations < params.max_evaluations {
+        iterations += 1;
+
+        // Selection
+        let first = population[binary_tournament(&population, rng)].clone();
+        let second = population[binary_tournament(&population, rng)].clone();
+
+        // Crossover
+        let mut children = if rng.gen_bool(params.crossover_probability) {
+            vec![
+                cross(&first, &second, params.crossover, problem, rng),
+                cross(&second, &first, params.crossover, problem, rng),
+            ]
+        }
+        else {
+            vec![first, second]
+        };
+
+        // Mutation and evaluation, never exceeding the evaluation budget
+        for chromosome in children.iter_mut() {
+            mutate(chromosome, params.mutation_probability, problem, rng);
+        }
+        for chromosome in children.iter_mut() {
+            if chromosome.fitness.is_none() && evaluations < params.max_evaluations {
+                chromosome.evaluate(problem);
+                evaluations += 1;
+            }
+        }
+        children.retain(|c| c.fitness.is_some());
+
+        // Replacement: the children compete with the two worst chromosomes
+        for child in children {
+            let worst = worst_index(&population);
+            if child.fitness() < population[worst].fitness() {
+                population[worst] = child;
+            }
+        }
+        observe(problem, &population, evaluations, observer);
+    }
+
+    result(problem, &population, evaluations, iterations, start)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::Point;
+    use rand::SeedableRng;
+
+    #[test]
+    fn repair_fills_every_cluster_even_with_one_element_each() {
+        let points = (0..5).map(|i| Point::from_vec(vec![i as f64])).collect();
+        let problem = Problem::from_points(points, &[], 5);
+        let mut rng = Pcg64::seed_from_u64(1);
+
+        let mut genes = vec![0; 5];
+        repair(&mut genes, &problem, &mut rng);
+
+        let mut sorted = genes.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn generational_never_exceeds_the_evaluation_budget() {
+        let points = (0..20).map(|i| Point::from_vec(vec![i as f64])).collect();
+        let problem = Problem::from_points(points, &[(0, 1, 1.0), (2, 3, -1.0)], 3);
+
+        for max_evaluations in [50, 51, 87, 130] {
+            let params = GeneticParams { population_size: 10, crossover_probability: 1.0, max_evaluations, ..GeneticParams::default() };
+            let result = generational_genetic(&problem, &params, &mut crate::par::NoObserver, &mut Pcg64::seed_from_u64(2));
+            assert_eq!(result.evaluations, max_evaluations);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use crate::par::{Problem, Partition, Point};
+use rand::Rng;
+use rand::seq::index::sample;
+use rand_pcg::Pcg64;
+use serde_derive::{Serialize, Deserialize};
+
+
+/// Strategy to place the initial centroids of the greedy COPKM
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Initialization {
+    /// Random points drawn uniformly within the bounding box of the data
+    #[default]
+    Uniform,
+    /// Data points chosen at random, independently of each other (a point may be chosen twice)
+    RandomPoints,
+    /// k-means++: the first centroid is a random data point, and every following one a data point
+    /// chosen with probability proportional to its squared distance to the nearest centroid
+    #[serde(rename = "k-means++")]
+    KMeansPlusPlus,
+    /// Forgy: k different data points chosen at random
+    Forgy,
+}
+
+impl Initialization {
+    /// Creates an empty partition with the initial centroids of the problem
+    /// - problem: &Problem - Instance of a problem
+    /// - rng: &mut rand_pcg::Pcg64 - Random number generator
+    pub fn partition(&self, problem: &Problem, rng: &mut Pcg64) -> Partition {
+        let centroids = match self {
+            Initialization::Uniform => return Partition::new(problem, rng),
+            Initialization::RandomPoints => (0..problem.k()).map(|_| random_point(problem, rng)).collect(),
+            Initialization::KMeansPlusPlus => k_means_plus_plus(problem, rng),
+            Initialization::Forgy => sample(rng, problem.size(), problem.k()).iter().map(|i| problem.data(i).clone()).collect(),
+        };
+
+        Partition::from_centroids(problem, centroids)
+    }
+
+    /// Places a new centroid in a cluster left empty: a uniform point within the bounding box of
+    /// the data for `Uniform`, and a random data point otherwise
+    /// - problem: &Problem - Instance of a problem
+    /// - partition: &mut Partition - Partition with the empty cluster
+    /// - cluster: usize - Index of the empty cluster
+    /// - rng: &mut rand_pcg::Pcg64 - Random number generator
+    pub fn reseed(&self, problem: &Problem, partition: &mut Partition, cluster: usize, rng: &mut Pcg64) {
+        match self {
+            Initialization::Uniform => partition.get_cluster_mut(cluster).randomize_centroid(problem, rng),
+            _ => partition.get_cluster_mut(cluster).set_centroid(random_point(problem, rng)),
+        }
+    }
+}
+
+/// Data point chosen at random
+fn random_point(problem: &Problem, rng: &mut Pcg64) -> Point {
+    problem.data(rng.gen_range(0..problem.size())).clone()
+}
+
+/// Centroids chosen by k-means++, with the distance of the problem
+fn k_means_plus_plus(problem: &Problem, rng: &mut Pcg64) -> Vec<Point> {
+    let mut centroids = vec![random_point(problem, rng)];
+    // Squared distance of each point to its nearest centroid
+    let mut weights: Vec<f64> = (0..problem.size())
+        .map(|i| problem.distance(problem.data(i), &centroids[0]).powi(2))
+        .collect();
+
+    while centroids.len() < problem.k() {
+        let total: f64 = weights.iter().sum();
+
+        // Every point is on a centroid: any of them is as good as the others
+        let chosen = if total > 0.0 {
+            let mut target = rng.gen::<f64>() * total;
+            weights.iter()
+                .position(|&w| { target -= w; target < 0.0 })
+                .unwrap_or_else(|| weights.iter().rposition(|&w| w > 0.0).unwrap())
+        }
+        else {
+            rng.gen_range(0..problem.size())
+        };
+
+        let centroid = problem.data(chosen).clone();
+        for (i, weight) in weights.iter_mut().enumerate() {
+            *weight = weight.min(problem.distance(problem.data(i), &centroid).powi(2));
+        }
+        centroids.push(centroid);
+    }
+
+    centroids
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    /// Problem with `n` different points on a line
+    fn line(n: usize, k: usize) -> Problem {
+        let points = (0..n).map(|i| Point::from_vec(vec![(i * i) as f64])).collect();
+        Problem::from_points(points, &[], k)
+    }
+
+    /// Index of the data point on which each centroid of the partition lies
+    fn chosen_points(problem: &Problem, partition: &Partition) -> Vec<usize> {
+        let mut chosen: Vec<usize> = partition.clusters().iter()
+            .map(|c| (0..problem.size()).find(|&i| problem.data(i) == c.centroid()).expect("centroid is not a data point"))
+            .collect();
+        chosen.sort_unstable();
+        chosen
+    }
+
+    #[test]
+    fn k_means_plus_plus_never_chooses_a_point_twice() {
+        let problem = line(6, 6);
+        for seed in 0..20 {
+            let partition = Initialization::KMeansPlusPlus.partition(&problem, &mut Pcg64::seed_from_u64(seed));
+            assert_eq!(chosen_points(&problem, &partition), vec![0, 1, 2, 3, 4, 5]);
+        }
+    }
+
+    #[test]
+    fn k_means_plus_plus_accepts_fewer_different_points_than_clusters() {
+        let points = vec![Point::from_vec(vec![1.0]); 4];
+        let problem = Problem::from_points(points, &[], 3);
+
+        let partition = Initialization::KMeansPlusPlus.partition(&problem, &mut Pcg64::seed_from_u64(0));
+        assert_eq!(partition.clusters().len(), 3);
+    }
+
+    #[test]
+    fn forgy_chooses_different_points() {
+        let problem = line(5, 5);
+        for seed in 0..20 {
+            let partition = Initialization::Forgy.partition(&problem, &mut Pcg64::seed_from_u64(seed));
+            assert_eq!(chosen_points(&problem, &partition), vec![0, 1, 2, 3, 4]);
+        }
+    }
+}
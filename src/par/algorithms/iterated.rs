@@ -0,0 +1,201 @@
+use crate::par::{Problem, Partition, SolveResult, Observer, Embedded};
+use super::{descent, check_probability};
+use super::annealing::{anneal, AnnealingParams};
+use super::genetic::repair;
+use rand::Rng;
+use rand_pcg::Pcg64;
+use std::time::Instant;
+use serde_derive::{Serialize, Deserialize};
+
+
+/// Parameters of the iterated searches
+/// - iterations: usize - Number of embedded searches, including the one from the initial partition
+/// - max_evaluations: usize - Fitness evaluations of each embedded search
+/// - segment_ratio: f64 - Length of the mutated segment, as a fraction of the number of elements
+/// - annealing: AnnealingParams - Parameters of the embedded simulated annealing (ILS-ES only);
+///   its `max_evaluations` is replaced by the one above, minus the evaluation of its initial partition
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IteratedParams {
+    pub iterations: usize,
+    pub max_evaluations: usize,
+    pub segment_ratio: f64,
+    pub annealing: AnnealingParams,
+}
+
+impl Default for IteratedParams {
+    fn default() -> Self {
+        IteratedParams {
+            iterations: 10,
+            max_evaluations: 10_000,
+            segment_ratio: 0.1,
+            annealing: AnnealingParams::default(),
+        }
+    }
+}
+
+impl IteratedParams {
+    /// Checks that there is at least one search, that the segment is a fraction of the elements and
+    /// the parameters of the embedded annealing
+    pub fn validate(&self) -> Result<(), String> {
+        if self.iterations == 0 {
+            return Err("the iterated search needs at least one iteration".to_string());
+        }
+        check_probability("the segment ratio", self.segment_ratio)?;
+        self.annealing.validate()
+    }
+}
+
+
+/// Segment mutation: assigns every element of a random contiguous segment to a random cluster,
+/// then repairs any empty cluster
+/// - problem: &Problem - Instance of a problem
+/// - partition: &Partition - Partition to perturb
+/// - length: usize - Number of elements in the segment
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+fn segment_mutation(problem: &Problem, partition: &Partition, length: usize, rng: &mut Pcg64) -> Partition {
+    let n = problem.size();
+    let mut labels = partition.labels();
+
+    let start = rng.gen_range(0..n);
+    for i in 0..length.min(n) {
+        labels[(start + i) % n] = rng.gen_range(0..problem.k());
+    }
+
+    repair(&mut labels, problem, rng);
+    Partition::from_labels(&labels, problem)
+}
+
+
+/// Iterated local search (ILS)
+/// - problem: &Problem - Instance of a problem
+/// - params: &IteratedParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores; each embedded search is an iteration
+/// Panics if the parameters are not valid (see `IteratedParams::validate`)
+pub fn iterated_local_search(problem: &Problem, params: &IteratedParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+    iterated(problem, params, observer, rng, |partition, observer, rng| {
+        let (optimum, fitness, evaluations, _) = descent(problem, partition, params.max_evaluations, observer, rng);
+        (optimum, fitness, evaluations)
+    })
+}
+
+/// Iterated local search using simulated annealing as the embedded search (ILS-ES)
+/// - problem: &Problem - Instance of a problem
+/// - params: &IteratedParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores, with the worse moves accepted by every annealing;
+/// each embedded search is an iteration
+/// Panics if the parameters are not valid (see `IteratedParams::validate`)
+pub fn iterated_annealing(problem: &Problem, params: &IteratedParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+    let annealing = AnnealingParams {
+        max_evaluations: params.max_evaluations.saturating_sub(1),
+        ..params.annealing
+    };
+
+    let mut accepted_worse = 0;
+    let mut result = iterated(problem, params, observer, rng, |partition, observer, rng| {
+        let fitness = problem.fitness(&partition);
+        observer.observe(1, fitness, fitness, &|| problem.calc_infeasiblity(partition.cluster_index()));
+
+        let mut embedded = Embedded::new(observer, 1, fitness);
+        let (optimum, optimum_fitness, evaluations, _, accepted) = anneal(problem, partition, fitness, &annealing, &mut embedded, rng);
+        accepted_worse += accepted;
+        (optimum, optimum_fitness, evaluations + 1)
+    });
+
+    result.accepted_worse = Some(accepted_worse);
+    result
+}
+
+/// Iterated scheme: the best partition found so far is perturbed and optimized again
+/// - optimize: FnMut(Partition, &mut dyn Observer, &mut Pcg64) -> (Partition, f64, usize) - Embedded search,
+///   returning its optimum, the fitness of the optimum and the evaluations spent
+fn iterated<F>(problem: &Problem, params: &IteratedParams, observer: &mut dyn Observer, rng: &mut Pcg64, mut optimize: F) -> SolveResult
+where
+    F: FnMut(Partition, &mut dyn Observer, &mut Pcg64) -> (Partition, f64, usize),
+{
+    let start = Instant::now();
+    let length = (params.segment_ratio * problem.size() as f64) as usize;
+
+    // First search from a random partition
+    let initial = Partition::new_rand(problem, rng);
+    let (mut best, mut best_fitness, mut evaluations) = optimize(initial, observer, rng);
+    let mut searches = 1;
+
+    for _ in 1..params.iterations {
+        let perturbed = segment_mutation(problem, &best, length, rng);
+        let mut embedded = Embedded::new(observer, evaluations, best_fitness);
+        let (optimum, fitness, spent) = optimize(perturbed, &mut embedded, rng);
+        evaluations += spent;
+        searches += 1;
+
+        if fitness < best_fitness {
+            best = optimum;
+            best_fitness = fitness;
+        }
+    }
+
+    SolveResult::new(problem, best, best_fitness, evaluations, searches, start.elapsed())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::{Point, NoObserver};
+    use rand::SeedableRng;
+
+    fn line(n: usize, k: usize) -> Problem {
+        let points = (0..n).map(|i| Point::from_vec(vec![i as f64])).collect();
+        Problem::from_points(points, &[], k)
+    }
+
+    #[test]
+    fn segment_mutation_only_changes_a_cyclic_segment() {
+        let problem = line(10, 2);
+        let labels: Vec<usize> = (0..10).map(|i| i % 2).collect();
+        let partition = Partition::from_labels(&labels, &problem);
+        let mut rng = Pcg64::seed_from_u64(3);
+
+        for _ in 0..50 {
+            let mutated = segment_mutation(&problem, &partition, 3, &mut rng).labels();
+            let changed: Vec<usize> = (0..10).filter(|&i| mutated[i] != labels[i]).collect();
+            assert!((0..10).any(|start| changed.iter().all(|&i| (i + 10 - start) % 10 < 3)), "{:?} is not a segment", changed);
+        }
+    }
+
+    #[test]
+    fn segment_mutation_repairs_the_emptied_clusters() {
+        let problem = line(6, 3);
+        let partition = Partition::from_labels(&[0, 0, 1, 1, 2, 2], &problem);
+        let mut rng = Pcg64::seed_from_u64(4);
+
+        for _ in 0..50 {
+            let mutated = segment_mutation(&problem, &partition, 6, &mut rng);
+            assert!(mutated.clusters().iter().all(|c| !c.is_empty()));
+            assert_eq!(mutated.labels().len(), 6);
+        }
+    }
+
+    #[test]
+    fn iterated_annealing_never_exceeds_the_evaluation_budget() {
+        let points = (0..30).map(|i| Point::from_vec(vec![(i % 7) as f64, (i / 7) as f64])).collect();
+        let problem = Problem::from_points(points, &[(0, 1, 1.0), (2, 3, -1.0), (4, 9, 1.0)], 3);
+        let params = IteratedParams {
+            iterations: 4,
+            max_evaluations: 50,
+            annealing: AnnealingParams { max_neighbours: Some(1000), max_successes: Some(1000), ..AnnealingParams::default() },
+            ..IteratedParams::default()
+        };
+
+        let result = iterated_annealing(&problem, &params, &mut NoObserver, &mut Pcg64::seed_from_u64(5));
+        assert_eq!(result.evaluations, params.iterations * params.max_evaluations);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::par::{Problem, SolveResult, Observer};
+use super::genetic::{generational, Chromosome, GeneticParams};
+use super::{soft_local_search, check_probability};
+use rand::seq::index::sample;
+use rand_pcg::Pcg64;
+use serde_derive::{Serialize, Deserialize};
+
+
+/// Parameters of the memetic algorithms
+/// - genetic: GeneticParams - Parameters of the underlying generational genetic algorithm
+/// - period: usize - Number of generations between local search passes
+/// - probability: f64 - Fraction of the population improved in each pass
+/// - best_only: bool - Improve the best chromosomes instead of random ones
+/// - failures_ratio: f64 - Maximum failures of the soft local search, as a fraction of the number of elements
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemeticParams {
+    pub genetic: GeneticParams,
+    pub period: usize,
+    pub probability: f64,
+    pub best_only: bool,
+    pub failures_ratio: f64,
+}
+
+impl Default for MemeticParams {
+    fn default() -> Self {
+        MemeticParams::all()
+    }
+}
+
+impl MemeticParams {
+    /// AM-(10,1.0): every chromosome is improved every 10 generations
+    pub fn all() -> MemeticParams {
+        MemeticParams {
+            genetic: GeneticParams::default(),
+            period: 10,
+            probability: 1.0,
+            best_only: false,
+            failures_ratio: 0.1,
+        }
+    }
+
+    /// AM-(10,0.1): a random 10% of the population is improved every 10 generations
+    pub fn random() -> MemeticParams {
+        MemeticParams {
+            probability: 0.1,
+            ..MemeticParams::all()
+        }
+    }
+
+    /// AM-(10,0.1mej): the best 10% of the population is improved every 10 generations
+    pub fn best() -> MemeticParams {
+        MemeticParams {
+            probability: 0.1,
+            best_only: true,
+            ..MemeticParams::all()
+        }
+    }
+
+    /// Checks the parameters of the genetic algorithm and that the local search passes can be
+    /// scheduled: they need a period of at least one generation
+    pub fn validate(&self) -> Result<(), String> {
+        self.genetic.validate()?;
+        if self.period == 0 {
+            return Err("the period of the local search must be at least 1 generation".to_string());
+        }
+        check_probability("the local search probability", self.probability)?;
+        if self.failures_ratio.is_nan() || self.failures_ratio < 0.0 {
+            return Err(format!("the failures ratio cannot be negative, not {}", self.failures_ratio));
+        }
+        Ok(())
+    }
+}
+
+
+/// Memetic algorithm: generational genetic algorithm whose population is periodically improved
+/// with a soft local search
+/// - problem: &Problem - Instance of a problem
+/// - params: &MemeticParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution, which sees the best chromosome of each generation
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores; each generation is an iteration
+/// Panics if the parameters are not valid (see `MemeticParams::validate`)
+pub fn memetic(problem: &Problem, params: &MemeticParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+    let max_failures = (params.failures_ratio * problem.size() as f64) as usize;
+
+    generational(problem, &params.genetic, observer, rng, |population, generation, remaining, rng| {
+        if generation % params.period != 0 {
+            return 0;
+        }
+
+        // Chromosomes to improve
+        let count = ((params.probability * population.len() as f64).round() as usize).min(population.len());
+        let selected: Vec<usize> = if params.best_only {
+            let mut indexes: Vec<usize> = (0..population.len()).collect();
+            indexes.sort_by(|&a, &b| population[a].fitness().partial_cmp(&population[b].fitness()).unwrap());
+            indexes.truncate(count);
+            indexes
+        }
+        else {
+            sample(rng, population.len(), count).into_vec()
+        };
+
+        // Soft local search over each one, sharing the remaining budget
+        let mut evaluations = 0;
+        for i in selected {
+            if evaluations >= remaining {
+                break;
+            }
+
+            let partition = population[i].to_partition(problem);
+            let (improved, fitness, spent) = soft_local_search(problem, &partition, max_failures, remaining - evaluations, rng);
+
+            population[i] = Chromosome {
+                genes: improved.labels(),
+                fitness: Some(fitness),
+            };
+            evaluations += spent;
+        }
+
+        evaluations
+    })
+}
@@ -0,0 +1,81 @@
+use crate::par::{Problem, Partition, SolveResult, Observer, Embedded};
+use super::descent;
+use rand_pcg::Pcg64;
+use std::time::Instant;
+use serde_derive::{Serialize, Deserialize};
+
+
+/// Parameters of the basic multi-start search
+/// - restarts: usize - Number of local searches, each one from a random partition
+/// - max_evaluations: usize - Fitness evaluations shared by all the local searches
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MultiStartParams {
+    pub restarts: usize,
+    pub max_evaluations: usize,
+}
+
+impl MultiStartParams {
+    /// Checks that every local search can be run: there must be at least one, and at least one
+    /// evaluation for each of them
+    pub fn validate(&self) -> Result<(), String> {
+        if self.restarts == 0 {
+            return Err("the multi-start search needs at least one restart".to_string());
+        }
+        if self.max_evaluations < self.restarts {
+            return Err(format!("{} evaluations cannot be split between {} local searches", self.max_evaluations, self.restarts));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for MultiStartParams {
+    fn default() -> Self {
+        MultiStartParams {
+            restarts: 10,
+            max_evaluations: 100_000,
+        }
+    }
+}
+
+
+/// Basic multi-start search (BMB)
+/// Runs a local search from several random partitions and returns the best result. The budget is
+/// split evenly, and evaluations a local search does not spend are handed to the following ones.
+/// Panics if the parameters are not valid (see `MultiStartParams::validate`)
+/// - problem: &Problem - Instance of a problem
+/// - params: &MultiStartParams - Parameters of the algorithm
+/// - observer: &mut dyn Observer - Observer of the execution
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+/// #### Return value
+/// SolveResult Best partition and its scores; each local search is an iteration
+pub fn multi_start_local_search(problem: &Problem, params: &MultiStartParams, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+    params.validate().unwrap_or_else(|e| panic!("{}", e));
+
+    let start = Instant::now();
+    let mut best: Option<(Partition, f64)> = None;
+    let mut evaluations = 0;
+    let mut searches = 0;
+
+    for restart in 0..params.restarts {
+        let budget = (params.max_evaluations - evaluations) / (params.restarts - restart);
+        if budget == 0 {
+            continue;
+        }
+
+        let initial = Partition::new_rand(problem, rng);
+        let best_fitness = best.as_ref().map_or(f64::INFINITY, |(_, f)| *f);
+        let mut embedded = Embedded::new(observer, evaluations, best_fitness);
+        let (partition, fitness, spent, _) = descent(problem, initial, budget, &mut embedded, rng);
+        evaluations += spent;
+        searches += 1;
+
+        if best.as_ref().is_none_or(|(_, f)| fitness < *f) {
+            best = Some((partition, fitness));
+        }
+    }
+
+    let (best, best_fitness) = best.expect("No local search has been run");
+    SolveResult::new(problem, best, best_fitness, evaluations, searches, start.elapsed())
+}
@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result};
+use std::io;
+
+
+/// Error while loading a problem from its files
+/// Lines and columns are 1-based, and columns count the fields of a line
+#[derive(Debug)]
+pub enum ProblemError {
+    /// A file could not be opened or read
+    Io { file: String, source: io::Error },
+    /// A field is not a valid number
+    Parse { file: String, line: usize, column: usize, value: String },
+    /// A coordinate of a data point is not finite (nan or inf)
+    InvalidCoordinate { file: String, line: usize, column: usize, value: f64 },
+    /// The data file contains no points
+    EmptyData { file: String },
+    /// A line has a different number of fields than expected
+    RaggedRow { file: String, line: usize, expected: usize, found: usize },
+    /// The constraint matrix does not have as many rows as data points
+    Dimension { file: String, expected: usize, found: usize },
+    /// A constraint value is not valid (not finite, or 0 in a list)
+    InvalidConstraint { file: String, line: usize, column: usize, value: f64 },
+    /// The constraint matrix is not symmetric
+    Asymmetric { file: String, line: usize, column: usize },
+    /// A constraint list references an element that does not exist
+    IndexOutOfRange { file: String, line: usize, column: usize, index: usize, size: usize },
+    /// A constraint list has a constraint between an element and itself
+    SelfConstraint { file: String, line: usize, element: usize },
+    /// A constraint list repeats the constraint of a pair, first read in line `first`
+    DuplicateConstraint { file: String, line: usize, first: usize, pair: (usize, usize) },
+    /// A constraint list has a must-link and a cannot-link for the same pair, the first one in line `first`
+    ContradictoryConstraint { file: String, line: usize, first: usize, pair: (usize, usize) },
+    /// The number of clusters is not between 1 and the number of data points
+    Clusters { k: usize, size: usize },
+    /// The ground-truth file does not have one label per data point
+    Labels { file: String, expected: usize, found: usize },
+    /// There is no distance metric with the given name
+    Metric { name: String },
+    /// A multiplier of the weights of violated constraints is negative or not finite
+    Penalty { value: f64 },
+}
+
+impl Display for ProblemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ProblemError::Io { file, source } =>
+                write!(f, "{}: {}", file, source),
+            ProblemError::Parse { file, line, column, value } =>
+                write!(f, "{}:{}:{}: '{}' is not a valid number", file, line, column, value),
+            ProblemError::InvalidCoordinate { file, line, column, value } =>
+                write!(f, "{}:{}:{}: coordinate {} is not finite", file, line, column, value),
+            ProblemError::EmptyData { file } =>
+                write!(f, "{}: no data points", file),
+            ProblemError::RaggedRow { file, line, expected, found } =>
+                write!(f, "{}:{}: expected {} fields, found {}", file, line, expected, found),
+            ProblemError::Dimension { file, expected, found } =>
+                write!(f, "{}: expected a {}x{} constraint matrix, found {} rows", file, expected, expected, found),
+            ProblemError::InvalidConstraint { file, line, column, value } =>
+                write!(f, "{}:{}:{}: invalid constraint value {}", file, line, column, value),
+            ProblemError::Asymmetric { file, line, column } =>
+                write!(f, "{}:{}:{}: constraint matrix is not symmetric", file, line, column),
+            ProblemError::IndexOutOfRange { file, line, column, index, size } =>
+                write!(f, "{}:{}:{}: element {} does not exist ({} data points)", file, line, column, index, size),
+            ProblemError::SelfConstraint { file, line, element } =>
+                write!(f, "{}:{}: constraint between element {} and itself", file, line, element),
+            ProblemError::DuplicateConstraint { file, line, first, pair } =>
+                write!(f, "{}:{}: constraint between {} and {} already given in line {}", file, line, pair.0, pair.1, first),
+            ProblemError::ContradictoryConstraint { file, line, first, pair } =>
+                write!(f, "{}:{}: constraint between {} and {} contradicts the one in line {}", file, line, pair.0, pair.1, first),
+            ProblemError::Clusters { k, size } =>
+                write!(f, "cannot make {} clusters out of {} data points", k, size),
+            ProblemError::Labels { file, expected, found } =>
+                write!(f, "{}: expected {} labels, found {}", file, expected, found),
+            ProblemError::Penalty { value } =>
+                write!(f, "invalid constraint penalty {}: it must be a non-negative number", value),
+            ProblemError::Metric { name } =>
+                write!(f, "unknown distance metric '{}' (available: {})", name, super::Metric::NAMES.join(", ")),
+        }
+    }
+}
+
+impl Error for ProblemError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ProblemError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+
+/// Error while loading a partition from a label file
+/// Lines are 1-based
+#[derive(Debug)]
+pub enum PartitionError {
+    /// The file could not be opened or read
+    Io { file: String, source: io::Error },
+    /// A label or a metadata value is not valid
+    Parse { file: String, line: usize, value: String },
+    /// The file does not have one label per element of the problem
+    Length { file: String, expected: usize, found: usize },
+    /// A label is not a cluster of the problem
+    Cluster { file: String, line: usize, cluster: usize, k: usize },
+    /// The number of clusters of the metadata is not the one of the problem
+    Clusters { file: String, expected: usize, found: usize },
+    /// A cluster has no elements
+    EmptyCluster { file: String, cluster: usize },
+}
+
+impl Display for PartitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            PartitionError::Io { file, source } =>
+                write!(f, "{}: {}", file, source),
+            PartitionError::Parse { file, line, value } =>
+                write!(f, "{}:{}: '{}' is not a valid value", file, line, value),
+            PartitionError::Length { file, expected, found } =>
+                write!(f, "{}: expected {} labels, found {}", file, expected, found),
+            PartitionError::Cluster { file, line, cluster, k } =>
+                write!(f, "{}:{}: cluster {} does not exist (k = {})", file, line, cluster, k),
+            PartitionError::Clusters { file, expected, found } =>
+                write!(f, "{}: partition of {} clusters, but the problem has {}", file, found, expected),
+            PartitionError::EmptyCluster { file, cluster } =>
+                write!(f, "{}: cluster {} is empty", file, cluster),
+        }
+    }
+}
+
+impl Error for PartitionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PartitionError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
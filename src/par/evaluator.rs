@@ -0,0 +1,279 @@
+use super::{Problem, Partition, ConstraintKind};
+use super::problem::Point;
+
+
+/// Incremental evaluation of single-element moves
+/// Keeps per-cluster aggregates so that the fitness change of moving one element only touches the
+/// two affected clusters and the constraints of that element, instead of the whole partition.
+/// Moving an element moves both centroids, so the distances of the members of those two clusters
+/// are calculated again: a move costs O((|A| + |B|)·d) for clusters A and B and points of
+/// dimension d, plus one step per constraint of the element, instead of O(n·d) plus every constraint
+/// - labels: Vec<usize> - Cluster of each element
+/// - members: Vec<Vec<usize>> - Elements of each cluster
+/// - positions: Vec<usize> - Position of each element in the members vector of its cluster
+/// - sums: Vec<Point> - Sum of the points of each cluster
+/// - distances: Vec<f64> - Sum of the distances of the elements of each cluster to its centroid
+/// - infeasibility: f64 - Weighted sum of the violated constraints
+#[derive(Clone)]
+pub struct Evaluator {
+    labels: Vec<usize>,
+    members: Vec<Vec<usize>>,
+    positions: Vec<usize>,
+    sums: Vec<Point>,
+    distances: Vec<f64>,
+    infeasibility: f64,
+}
+
+impl Evaluator {
+    /// Creates the evaluator of a partition, computing every aggregate from scratch
+    /// - problem: &Problem - Instance of the problem
+    /// - partition: &Partition - Partition to evaluate
+    pub fn new(problem: &Problem, partition: &Partition) -> Evaluator {
+        let labels = partition.labels();
+        Evaluator::from_labels(problem, labels)
+    }
+
+    /// Creates the evaluator of a label vector, computing every aggregate from scratch
+    /// - problem: &Problem - Instance of the problem
+    /// - labels: Vec<usize> - Cluster of each element
+    pub fn from_labels(problem: &Problem, labels: Vec<usize>) -> Evaluator {
+        let k = problem.k();
+        let mut members = vec![Vec::new(); k];
+        let mut positions = vec![0; labels.len()];
+        let mut sums = vec![Point::zeros(problem.dimension()); k];
+
+        for (element, &cluster) in labels.iter().enumerate() {
+            positions[element] = members[cluster].len();
+            members[cluster].push(element);
+            sums[cluster] += problem.data(element);
+        }
+
+        let distances = (0..k)
+            .map(|c| Evaluator::distance_sum(problem, &members[c], &sums[c], members[c].len(), None))
+            .collect();
+
+        let mut infeasibility = 0.0;
+        for (element, &cluster) in labels.iter().enumerate() {
+            for (other, kind, penalty) in problem.constraints_of(element).filter(|&(other, _, _)| other < element) {
+                if Evaluator::violated(kind, cluster, labels[other]) {
+                    infeasibility += penalty;
+                }
+            }
+        }
+
+        Evaluator {
+            labels,
+            members,
+            positions,
+            sums,
+            distances,
+            infeasibility,
+        }
+    }
+
+    /// Returns the cluster of each element
+    pub fn labels(&self) -> &[usize] {
+        &self.labels
+    }
+
+    /// Returns the weighted sum of the violated constraints
+    pub fn infeasibility(&self) -> f64 {
+        self.infeasibility
+    }
+
+    /// Returns the general deviation of the partition
+    pub fn general_deviation(&self, problem: &Problem) -> f64 {
+        let deviation = self.distances.iter()
+            .zip(self.members.iter())
+            .fold(0.0, |acc, (d, m)| acc + d / m.len() as f64);
+
+        deviation / problem.k() as f64
+    }
+
+    /// Returns the fitness of the partition, as `Problem::fitness` would
+    pub fn fitness(&self, problem: &Problem) -> f64 {
+        self.general_deviation(problem) + self.infeasibility * problem.lambda()
+    }
+
+    /// Returns the fitness change of moving `element` to `cluster`
+    /// Costs O((|A| + |B|)·d), A being the cluster of the element and B the new one
+    /// #### Return value:
+    /// - `Some(delta)` if the move is valid
+    /// - `None` if the element is already in `cluster` or would leave its cluster empty
+    pub fn move_delta(&self, problem: &Problem, element: usize, cluster: usize) -> Option<f64> {
+        let current = self.labels[element];
+        if current == cluster || self.members[current].len() == 1 {
+            return None;
+        }
+
+        let (old_distance, new_distance) = self.moved_distances(problem, element, current, cluster);
+        let old_sizes = (self.members[current].len() as f64, self.members[cluster].len() as f64);
+        let new_sizes = (old_sizes.0 - 1.0, old_sizes.1 + 1.0);
+
+        let deviation_delta = (new_distance.0 / new_sizes.0 + new_distance.1 / new_sizes.1
+            - old_distance.0 / old_sizes.0 - old_distance.1 / old_sizes.1) / problem.k() as f64;
+
+        Some(deviation_delta + self.infeasibility_delta(problem, element, cluster) * problem.lambda())
+    }
+
+    /// Moves `element` to `cluster`, updating every aggregate
+    /// The move must be valid (see `move_delta`), and costs as much as evaluating it
+    pub fn apply_move(&mut self, problem: &Problem, element: usize, cluster: usize) {
+        let current = self.labels[element];
+        let (_, new_distance) = self.moved_distances(problem, element, current, cluster);
+        let inf_delta = self.infeasibility_delta(problem, element, cluster);
+
+        // Remove from the current cluster, moving the last member to its position
+        let position = self.positions[element];
+        self.members[current].swap_remove(position);
+        if let Some(&moved) = self.members[current].get(position) {
+            self.positions[moved] = position;
+        }
+
+        // Insert into the new cluster
+        self.positions[element] = self.members[cluster].len();
+        self.members[cluster].push(element);
+        self.labels[element] = cluster;
+
+        self.sums[current] -= problem.data(element);
+        self.sums[cluster] += problem.data(element);
+        self.distances[current] = new_distance.0;
+        self.distances[cluster] = new_distance.1;
+        self.infeasibility += inf_delta;
+    }
+
+    /// Builds the partition represented by the evaluator
+    pub fn to_partition(&self, problem: &Problem) -> Partition {
+        Partition::from_labels(&self.labels, problem)
+    }
+
+    /// Distance sums of the current and the new cluster, before and after moving `element`
+    fn moved_distances(&self, problem: &Problem, element: usize, current: usize, cluster: usize) -> ((f64, f64), (f64, f64)) {
+        let point = problem.data(element);
+
+        let current_sum = &self.sums[current] - point;
+        let cluster_sum = &self.sums[cluster] + point;
+
+        let current_distance = Evaluator::distance_sum(
+            problem, &self.members[current], &current_sum, self.members[current].len() - 1, Some(element)
+        );
+        let cluster_distance = Evaluator::distance_sum(
+            problem, &self.members[cluster], &cluster_sum, self.members[cluster].len() + 1, None
+        ) + problem.distance(point, &(cluster_sum / (self.members[cluster].len() + 1) as f64));
+
+        ((self.distances[current], self.distances[cluster]), (current_distance, cluster_distance))
+    }
+
+    /// Sum of the distances of `members` (except `skip`) to the centroid `sum / size`
+    fn distance_sum(problem: &Problem, members: &[usize], sum: &Point, size: usize, skip: Option<usize>) -> f64 {
+        let centroid = sum / size as f64;
+
+        members.iter()
+            .filter(|&&e| Some(e) != skip)
+            .fold(0.0, |acc, &e| acc + problem.distance(problem.data(e), &centroid))
+    }
+
+    /// Change in the weighted sum of the violated constraints when moving `element` to `cluster`
+    fn infeasibility_delta(&self, problem: &Problem, element: usize, cluster: usize) -> f64 {
+        let current = self.labels[element];
+
+        problem.constraints_of(element).fold(0.0, |acc, (other, kind, penalty)| {
+            let before = Evaluator::violated(kind, current, self.labels[other]);
+            let after = Evaluator::violated(kind, cluster, self.labels[other]);
+            match (before, after) {
+                (false, true) => acc + penalty,
+                (true, false) => acc - penalty,
+                _ => acc,
+            }
+        })
+    }
+
+    /// Returns `true` if a constraint between elements in clusters `a` and `b` is violated
+    fn violated(kind: ConstraintKind, a: usize, b: usize) -> bool {
+        match kind {
+            ConstraintKind::MustLink => a != b,
+            ConstraintKind::CannotLink => a == b,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    /// Random problem with weighted constraints of both kinds and different penalties for each
+    fn weighted_problem(rng: &mut Pcg64) -> Problem {
+        let points: Vec<Point> = (0..40).map(|_| Point::from_fn(3, |_, _| rng.gen_range(-5.0..5.0))).collect();
+        let mut constraints = Vec::new();
+        for a in 0..40 {
+            for b in (a + 1)..40 {
+                if rng.gen_bool(0.1) {
+                    let weight = rng.gen_range(0.1..3.0);
+                    constraints.push((a, b, if rng.gen_bool(0.5) { weight } else { -weight }));
+                }
+            }
+        }
+
+        let mut problem = Problem::from_points(points, &constraints, 4);
+        problem.set_penalties(2.0, 0.5).unwrap();
+        problem
+    }
+
+    fn random_labels(problem: &Problem, rng: &mut Pcg64) -> Vec<usize> {
+        // The first k elements make sure that no cluster is empty
+        (0..problem.size()).map(|e| if e < problem.k() { e } else { rng.gen_range(0..problem.k()) }).collect()
+    }
+
+    #[test]
+    fn move_delta_matches_fitness_difference() {
+        let mut rng = Pcg64::seed_from_u64(1);
+        let problem = weighted_problem(&mut rng);
+        let labels = random_labels(&problem, &mut rng);
+        let evaluator = Evaluator::from_labels(&problem, labels.clone());
+        let before = problem.fitness(&Partition::from_labels(&labels, &problem));
+        assert!((evaluator.fitness(&problem) - before).abs() < 1e-9);
+
+        for element in 0..problem.size() {
+            for cluster in 0..problem.k() {
+                let mut moved = labels.clone();
+                moved[element] = cluster;
+                let after = Partition::from_labels(&moved, &problem);
+
+                match evaluator.move_delta(&problem, element, cluster) {
+                    Some(delta) => assert!((delta - (problem.fitness(&after) - before)).abs() < 1e-9),
+                    None => assert!(cluster == labels[element] || after.clusters().iter().any(|c| c.is_empty())),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn apply_move_does_not_drift() {
+        let mut rng = Pcg64::seed_from_u64(2);
+        let problem = weighted_problem(&mut rng);
+        let labels = random_labels(&problem, &mut rng);
+        let mut evaluator = Evaluator::from_labels(&problem, labels);
+        let mut expected = evaluator.fitness(&problem);
+
+        let mut moves = 0;
+        while moves < 5000 {
+            let element = rng.gen_range(0..problem.size());
+            let cluster = rng.gen_range(0..problem.k());
+            if let Some(delta) = evaluator.move_delta(&problem, element, cluster) {
+                evaluator.apply_move(&problem, element, cluster);
+                expected += delta;
+                moves += 1;
+            }
+        }
+
+        let fresh = Evaluator::from_labels(&problem, evaluator.labels().to_vec());
+        let partition = Partition::from_labels(evaluator.labels(), &problem);
+        assert!((evaluator.infeasibility() - problem.calc_infeasiblity(partition.cluster_index())).abs() < 1e-9);
+        assert!((evaluator.fitness(&problem) - fresh.fitness(&problem)).abs() < 1e-9);
+        assert!((evaluator.fitness(&problem) - problem.fitness(&partition)).abs() < 1e-9);
+        assert!((expected - problem.fitness(&partition)).abs() < 1e-6);
+    }
+}
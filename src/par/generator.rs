@@ -0,0 +1,132 @@
+use super::problem::Point;
+use rand::Rng;
+use rand::seq::index::sample;
+use rand_pcg::Pcg64;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+
+/// Parameters of a synthetic instance
+/// - points: usize - Number of points
+/// - dimension: usize - Dimension of each point
+/// - k: usize - Number of clusters (Gaussian blobs)
+/// - spread: f64 - Standard deviation of each blob
+/// - labelled_ratio: f64 - Fraction of the points whose pairs are constrained
+#[derive(Clone, Copy, Debug)]
+pub struct GeneratorParams {
+    pub points: usize,
+    pub dimension: usize,
+    pub k: usize,
+    pub spread: f64,
+    pub labelled_ratio: f64,
+}
+
+/// Synthetic PAR instance
+/// - data: Vec<Point> - Points of the instance
+/// - labels: Vec<usize> - Blob that generated each point
+/// - constraints: Vec<(usize, usize, i8)> - Constraints between labelled points (1 must-link, -1 cannot-link)
+pub struct Instance {
+    pub data: Vec<Point>,
+    pub labels: Vec<usize>,
+    pub constraints: Vec<(usize, usize, i8)>,
+}
+
+/// Generates an instance of `k` Gaussian blobs with centres uniformly distributed in [0,1)^d
+/// Every pair of a random subset of the points is constrained according to their blobs
+/// - params: &GeneratorParams - Parameters of the instance
+/// - rng: &mut rand_pcg::Pcg64 - Random number generator
+pub fn generate(params: &GeneratorParams, rng: &mut Pcg64) -> Instance {
+    let centres: Vec<Point> = (0..params.k)
+        .map(|_| Point::from_fn(params.dimension, |_, _| rng.gen()))
+        .collect();
+
+    let mut data = Vec::with_capacity(params.points);
+    let mut labels = Vec::with_capacity(params.points);
+    for i in 0..params.points {
+        let label = i % params.k;
+        let noise = Point::from_fn(params.dimension, |_, _| params.spread * standard_normal(rng));
+
+        data.push(&centres[label] + noise);
+        labels.push(label);
+    }
+
+    let labelled = ((params.labelled_ratio * params.points as f64) as usize).min(params.points);
+    let mut chosen = sample(rng, params.points, labelled).into_vec();
+    chosen.sort_unstable();
+
+    let mut constraints = Vec::new();
+    for (i, &a) in chosen.iter().enumerate() {
+        for &b in chosen.iter().skip(i + 1) {
+            constraints.push((a, b, if labels[a] == labels[b] { 1 } else { -1 }));
+        }
+    }
+
+    Instance {
+        data,
+        labels,
+        constraints,
+    }
+}
+
+/// Samples a standard normal value with the Box-Muller transform
+fn standard_normal(rng: &mut Pcg64) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+impl Instance {
+    /// Writes the points as a data file, one comma-separated point per line
+    pub fn write_data(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for point in self.data.iter() {
+            let fields: Vec<String> = point.iter().map(|x| x.to_string()).collect();
+            writeln!(out, "{}", fields.join(","))?;
+        }
+
+        out.flush()
+    }
+
+    /// Writes the constraints as a constraint matrix
+    pub fn write_matrix(&self, path: &str) -> io::Result<()> {
+        let n = self.data.len();
+        let mut matrix = vec![vec![0i8; n]; n];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = 1;
+        }
+        for &(a, b, value) in self.constraints.iter() {
+            matrix[a][b] = value;
+            matrix[b][a] = value;
+        }
+
+        let mut out = BufWriter::new(File::create(path)?);
+        for row in matrix {
+            let fields: Vec<String> = row.iter().map(|x| x.to_string()).collect();
+            writeln!(out, "{}", fields.join(","))?;
+        }
+
+        out.flush()
+    }
+
+    /// Writes the constraints as a constraint list
+    pub fn write_list(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for &(a, b, value) in self.constraints.iter() {
+            writeln!(out, "{},{},{}", a, b, value)?;
+        }
+
+        out.flush()
+    }
+
+    /// Writes the blob of each point, one per line
+    pub fn write_labels(&self, path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for label in self.labels.iter() {
+            writeln!(out, "{}", label)?;
+        }
+
+        out.flush()
+    }
+}
@@ -0,0 +1,176 @@
+use super::{Problem, Partition, PartitionError};
+use std::fs::{read_to_string, File};
+use std::io::{self, BufWriter, Write};
+
+
+/// Metadata of a label file, every field being optional so that plain label files can be read
+/// - instance: Option<String> - Name of the instance
+/// - k: Option<usize> - Number of clusters
+/// - seed: Option<u64> - Seed of the execution that found the partition
+/// - algorithm: Option<String> - Algorithm that found the partition
+/// - fitness: Option<f64> - Fitness of the partition when it was saved
+/// - metric: Option<String> - Distance metric of the problem when it was saved
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartitionInfo {
+    pub instance: Option<String>,
+    pub k: Option<usize>,
+    pub seed: Option<u64>,
+    pub algorithm: Option<String>,
+    pub fitness: Option<f64>,
+    pub metric: Option<String>,
+}
+
+impl Partition {
+    /// Writes the partition as a label file: a `# key: value` line for each field of the metadata,
+    /// followed by the cluster of each element, one per line
+    /// - path: &str - File to write
+    /// - info: &PartitionInfo - Metadata of the partition
+    pub fn save(&self, path: &str, info: &PartitionInfo) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        if let Some(instance) = &info.instance {
+            writeln!(out, "# instance: {}", instance)?;
+        }
+        if let Some(k) = info.k {
+            writeln!(out, "# k: {}", k)?;
+        }
+        if let Some(seed) = info.seed {
+            writeln!(out, "# seed: {}", seed)?;
+        }
+        if let Some(algorithm) = &info.algorithm {
+            writeln!(out, "# algorithm: {}", algorithm)?;
+        }
+        if let Some(fitness) = info.fitness {
+            writeln!(out, "# fitness: {}", fitness)?;
+        }
+        if let Some(metric) = &info.metric {
+            writeln!(out, "# metric: {}", metric)?;
+        }
+
+        for label in self.labels() {
+            writeln!(out, "{}", label)?;
+        }
+
+        out.flush()
+    }
+
+    /// Reads a label file written by `Partition::save`, computing the centroid of every cluster
+    /// Labels may also be separated by commas or spaces, and unknown metadata keys are ignored
+    /// - path: &str - File to read
+    /// - problem: &Problem - Instance of the problem the partition belongs to
+    /// #### Return value
+    /// (Partition, PartitionInfo) Partition and its metadata
+    pub fn load(path: &str, problem: &Problem) -> Result<(Partition, PartitionInfo), PartitionError> {
+        let content = read_to_string(path).map_err(|source| PartitionError::Io { file: path.to_string(), source })?;
+        let parse_error = |line: usize, value: &str| PartitionError::Parse { file: path.to_string(), line, value: value.to_string() };
+
+        let mut info = PartitionInfo::default();
+        let mut labels = Vec::with_capacity(problem.size());
+
+        for (i, line) in content.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+
+            if let Some(comment) = line.strip_prefix('#') {
+                let (key, value) = match comment.split_once(':') {
+                    Some((key, value)) => (key.trim(), value.trim()),
+                    None => continue,
+                };
+
+                match key {
+                    "instance" => info.instance = Some(value.to_string()),
+                    "algorithm" => info.algorithm = Some(value.to_string()),
+                    "metric" => info.metric = Some(value.to_string()),
+                    "k" => info.k = Some(value.parse().map_err(|_| parse_error(line_number, value))?),
+                    "seed" => info.seed = Some(value.parse().map_err(|_| parse_error(line_number, value))?),
+                    "fitness" => info.fitness = Some(value.parse().map_err(|_| parse_error(line_number, value))?),
+                    _ => {},
+                }
+                continue;
+            }
+
+            for field in line.split(|c: char| c == ',' || c.is_whitespace()).filter(|f| !f.is_empty()) {
+                let cluster: usize = field.parse().map_err(|_| parse_error(line_number, field))?;
+                if cluster >= problem.k() {
+                    return Err(PartitionError::Cluster { file: path.to_string(), line: line_number, cluster, k: problem.k() });
+                }
+                labels.push(cluster);
+            }
+        }
+
+        if let Some(k) = info.k.filter(|&k| k != problem.k()) {
+            return Err(PartitionError::Clusters { file: path.to_string(), expected: problem.k(), found: k });
+        }
+        if labels.len() != problem.size() {
+            return Err(PartitionError::Length { file: path.to_string(), expected: problem.size(), found: labels.len() });
+        }
+
+        let partition = Partition::from_labels(&labels, problem);
+        if let Some(cluster) = partition.clusters().iter().position(|c| c.is_empty()) {
+            return Err(PartitionError::EmptyCluster { file: path.to_string(), cluster });
+        }
+
+        Ok((partition, info))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::Point;
+    use crate::par::testing::TempFile;
+
+    fn problem() -> Problem {
+        let points = (0..6).map(|i| Point::from_vec(vec![i as f64, (i * i) as f64])).collect();
+        Problem::from_points(points, &[(0, 1, 1.0)], 3)
+    }
+
+    #[test]
+    fn saved_partitions_are_loaded_back() {
+        let problem = problem();
+        let partition = Partition::from_labels(&[2, 0, 1, 1, 0, 2], &problem);
+        let info = PartitionInfo {
+            instance: Some("line".to_string()),
+            k: Some(3),
+            seed: Some(42),
+            algorithm: Some("es".to_string()),
+            fitness: Some(problem.fitness(&partition)),
+            metric: Some(problem.metric().to_string()),
+        };
+
+        let file = TempFile::new("round-trip.labels");
+        partition.save(file.path(), &info).unwrap();
+        let (loaded, loaded_info) = Partition::load(file.path(), &problem).unwrap();
+
+        assert_eq!(loaded.labels(), partition.labels());
+        assert_eq!(loaded_info, info);
+        for (a, b) in loaded.clusters().iter().zip(partition.clusters()) {
+            assert_eq!(a.centroid(), b.centroid());
+        }
+    }
+
+    #[test]
+    fn plain_label_files_are_loaded_without_metadata() {
+        let file = TempFile::with_content("plain.labels", "0, 1 2\n2 1\n# comment\n0\n");
+        let (partition, info) = Partition::load(file.path(), &problem()).unwrap();
+
+        assert_eq!(partition.labels(), vec![0, 1, 2, 2, 1, 0]);
+        assert_eq!(info, PartitionInfo::default());
+    }
+
+    #[test]
+    fn malformed_label_files_are_rejected() {
+        let problem = problem();
+        let load = |name: &str, content: &str| {
+            let file = TempFile::with_content(name, content);
+            Partition::load(file.path(), &problem).map(|_| ())
+        };
+
+        assert!(matches!(load("cluster.labels", "0\n1\n3\n0\n1\n2\n"), Err(PartitionError::Cluster { line: 3, cluster: 3, .. })));
+        assert!(matches!(load("length.labels", "0\n1\n2\n"), Err(PartitionError::Length { expected: 6, found: 3, .. })));
+        assert!(matches!(load("k.labels", "# k: 2\n0\n1\n2\n0\n1\n2\n"), Err(PartitionError::Clusters { expected: 3, found: 2, .. })));
+        assert!(matches!(load("empty.labels", "0\n1\n0\n1\n0\n1\n"), Err(PartitionError::EmptyCluster { cluster: 2, .. })));
+        assert!(matches!(load("parse.labels", "0\nx\n"), Err(PartitionError::Parse { line: 2, .. })));
+    }
+}
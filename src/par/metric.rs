@@ -0,0 +1,182 @@
+use super::Point;
+use na::DMatrix;
+use std::fmt::{Display, Formatter, Result};
+
+
+/// Distance between the points of a problem, and between points and centroids
+/// Centroids are always the mean of their elements, whatever the metric
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Metric {
+    /// Straight-line distance
+    #[default]
+    Euclidean,
+    /// Square of the Euclidean distance, which penalizes distant elements more
+    SquaredEuclidean,
+    /// Sum of the absolute differences of each feature
+    Manhattan,
+    /// Largest absolute difference of a feature
+    Chebyshev,
+    /// 1 minus the cosine of the angle between both points, for data where only the direction matters
+    Cosine,
+    /// Euclidean distance after decorrelating the features with the covariance of the data
+    Mahalanobis { inverse_covariance: DMatrix<f64> },
+}
+
+impl Metric {
+    /// Names of the metrics, as accepted by `Metric::from_name`
+    pub const NAMES: [&'static str; 6] = ["euclidean", "squared-euclidean", "manhattan", "chebyshev", "cosine", "mahalanobis"];
+
+    /// Builds a metric from its name
+    /// - name: &str - Name of the metric (see `Metric::NAMES`)
+    /// - data: &[Point] - Points of the problem, needed to calculate the Mahalanobis covariance
+    /// #### Return value
+    /// Option<Metric> The metric, or `None` if there is no metric with that name
+    pub fn from_name(name: &str, data: &[Point]) -> Option<Metric> {
+        match name {
+            "euclidean" => Some(Metric::Euclidean),
+            "squared-euclidean" => Some(Metric::SquaredEuclidean),
+            "manhattan" => Some(Metric::Manhattan),
+            "chebyshev" => Some(Metric::Chebyshev),
+            "cosine" => Some(Metric::Cosine),
+            "mahalanobis" => Some(Metric::mahalanobis(data)),
+            _ => None,
+        }
+    }
+
+    /// Builds the Mahalanobis metric of a dataset from the sample covariance of its features
+    /// Singular covariances (constant or linearly dependent features) use their pseudo-inverse
+    /// - data: &[Point] - Points of the problem
+    pub fn mahalanobis(data: &[Point]) -> Metric {
+        let dimension = data.first().map_or(0, |p| p.len());
+        let mean = data.iter().fold(Point::zeros(dimension), |acc, x| acc + x) / data.len().max(1) as f64;
+
+        let mut covariance = DMatrix::zeros(dimension, dimension);
+        for point in data.iter() {
+            let centered = point - &mean;
+            covariance += &centered * centered.transpose();
+        }
+        covariance /= data.len().saturating_sub(1).max(1) as f64;
+
+        let inverse_covariance = covariance.clone().try_inverse()
+            .or_else(|| covariance.pseudo_inverse(1e-12).ok())
+            .unwrap_or_else(|| DMatrix::identity(dimension, dimension));
+
+        Metric::Mahalanobis { inverse_covariance }
+    }
+
+    /// Name of the metric
+    pub fn name(&self) -> &'static str {
+        match self {
+            Metric::Euclidean => "euclidean",
+            Metric::SquaredEuclidean => "squared-euclidean",
+            Metric::Manhattan => "manhattan",
+            Metric::Chebyshev => "chebyshev",
+            Metric::Cosine => "cosine",
+            Metric::Mahalanobis { .. } => "mahalanobis",
+        }
+    }
+
+    /// Returns the distance between two points
+    pub fn distance(&self, a: &Point, b: &Point) -> f64 {
+        match self {
+            Metric::Euclidean => a.metric_distance(b),
+            Metric::SquaredEuclidean => (a - b).norm_squared(),
+            Metric::Manhattan => (a - b).lp_norm(1),
+            Metric::Chebyshev => (a - b).amax(),
+            Metric::Cosine => {
+                let norms = a.norm() * b.norm();
+                // The origin has no direction: it is only close to itself
+                if norms == 0.0 {
+                    if a == b { 0.0 } else { 1.0 }
+                }
+                else {
+                    (1.0 - a.dot(b) / norms).max(0.0)
+                }
+            },
+            Metric::Mahalanobis { inverse_covariance } => {
+                let difference = a - b;
+                difference.dot(&(inverse_covariance * &difference)).max(0.0).sqrt()
+            },
+        }
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::testing::assert_close;
+
+    fn point(values: &[f64]) -> Point {
+        Point::from_vec(values.to_vec())
+    }
+
+    #[test]
+    fn minkowski_distances() {
+        let (a, b) = (point(&[1.0, 2.0]), point(&[4.0, -2.0]));
+
+        assert_close(Metric::Euclidean.distance(&a, &b), 5.0);
+        assert_close(Metric::SquaredEuclidean.distance(&a, &b), 25.0);
+        assert_close(Metric::Manhattan.distance(&a, &b), 7.0);
+        assert_close(Metric::Chebyshev.distance(&a, &b), 4.0);
+    }
+
+    #[test]
+    fn cosine_distance_only_depends_on_the_direction() {
+        let metric = Metric::Cosine;
+        let x = point(&[1.0, 0.0]);
+
+        assert_close(metric.distance(&x, &point(&[3.0, 0.0])), 0.0);
+        assert_close(metric.distance(&x, &point(&[0.0, 2.0])), 1.0);
+        assert_close(metric.distance(&x, &point(&[-1.0, 0.0])), 2.0);
+    }
+
+    #[test]
+    fn cosine_origin_is_only_close_to_itself() {
+        let metric = Metric::Cosine;
+        let origin = point(&[0.0, 0.0]);
+
+        assert_eq!(metric.distance(&origin, &origin), 0.0);
+        assert_eq!(metric.distance(&origin, &point(&[1.0, 1.0])), 1.0);
+        assert_eq!(metric.distance(&point(&[1.0, 1.0]), &origin), 1.0);
+    }
+
+    #[test]
+    fn mahalanobis_scales_by_the_variance_of_each_feature() {
+        // Uncorrelated features with variance 4/3
+        let data = vec![point(&[0.0, 0.0]), point(&[2.0, 0.0]), point(&[0.0, 2.0]), point(&[2.0, 2.0])];
+        let metric = Metric::mahalanobis(&data);
+
+        assert_close(metric.distance(&data[0], &data[1]), 3.0f64.sqrt());
+        assert_close(metric.distance(&data[0], &data[3]), 6.0f64.sqrt());
+    }
+
+    #[test]
+    fn mahalanobis_with_a_singular_covariance_ignores_the_dependent_directions() {
+        // Constant second feature
+        let constant = vec![point(&[0.0, 5.0]), point(&[1.0, 5.0]), point(&[2.0, 5.0])];
+        let metric = Metric::mahalanobis(&constant);
+        assert_close(metric.distance(&point(&[0.0, 5.0]), &point(&[2.0, 7.0])), 2.0);
+
+        // Identical features: only the distance along the diagonal is measured
+        let dependent = vec![point(&[0.0, 0.0]), point(&[1.0, 1.0]), point(&[2.0, 2.0])];
+        let metric = Metric::mahalanobis(&dependent);
+        assert_close(metric.distance(&dependent[0], &dependent[2]), 2.0);
+        assert_close(metric.distance(&point(&[1.0, -1.0]), &point(&[0.0, 0.0])), 0.0);
+    }
+
+    #[test]
+    fn every_name_builds_its_metric() {
+        let data = vec![point(&[0.0, 1.0]), point(&[1.0, 0.0])];
+        for name in Metric::NAMES {
+            assert_eq!(Metric::from_name(name, &data).unwrap().name(), name);
+        }
+        assert!(Metric::from_name("hamming", &data).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+use super::{Problem, Partition};
+
+
+/// Contingency matrix of a partition against the classes of its elements
+/// - counts: Vec<Vec<usize>> - Number of elements of each cluster (row) and class (column)
+/// - size: usize - Number of elements
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contingency {
+    counts: Vec<Vec<usize>>,
+    size: usize,
+}
+
+impl Contingency {
+    /// Counts the elements of each cluster and class
+    /// - partition: &Partition - Partition whose clusters are the rows
+    /// - classes: &[usize] - Class of each element, classes being the columns
+    pub fn new(partition: &Partition, classes: &[usize]) -> Contingency {
+        Contingency::from_labels(&partition.labels(), classes)
+    }
+
+    /// Counts the elements of each cluster and class
+    /// - labels: &[usize] - Cluster of each element
+    /// - classes: &[usize] - Class of each element
+    pub fn from_labels(labels: &[usize], classes: &[usize]) -> Contingency {
+        assert_eq!(labels.len(), classes.len(), "There must be a class for each element");
+
+        let rows = labels.iter().max().map_or(0, |&l| l + 1);
+        let columns = classes.iter().max().map_or(0, |&c| c + 1);
+        let mut counts = vec![vec![0; columns]; rows];
+        for (&label, &class) in labels.iter().zip(classes) {
+            counts[label][class] += 1;
+        }
+
+        Contingency {
+            counts,
+            size: labels.len(),
+        }
+    }
+
+    /// Number of elements of each cluster (row) and class (column)
+    pub fn counts(&self) -> &[Vec<usize>] {
+        &self.counts
+    }
+
+    /// Number of elements of each cluster
+    pub fn cluster_sizes(&self) -> Vec<usize> {
+        self.counts.iter().map(|row| row.iter().sum()).collect()
+    }
+
+    /// Number of elements of each class
+    pub fn class_sizes(&self) -> Vec<usize> {
+        let columns = self.counts.first().map_or(0, |row| row.len());
+        (0..columns).map(|c| self.counts.iter().map(|row| row[c]).sum()).collect()
+    }
+
+    /// Adjusted Rand index: agreement between clusters and classes over every pair of elements,
+    /// corrected for chance. 1 is a perfect match, and 0 the expected value of a random partition
+    pub fn adjusted_rand_index(&self) -> f64 {
+        let pairs = |n: usize| (n * n.saturating_sub(1)) as f64 / 2.0;
+
+        let index: f64 = self.counts.iter().flatten().map(|&n| pairs(n)).sum();
+        let clusters: f64 = self.cluster_sizes().into_iter().map(pairs).sum();
+        let classes: f64 = self.class_sizes().into_iter().map(pairs).sum();
+
+        let expected = clusters * classes / pairs(self.size);
+        let max = (clusters + classes) / 2.0;
+
+        // Both labelings are a single group or singletons: they can only match perfectly
+        if max == expected {
+            1.0
+        }
+        else {
+            (index - expected) / (max - expected)
+        }
+    }
+
+    /// Normalized mutual information: mutual information of clusters and classes divided by the
+    /// arithmetic mean of their entropies, between 0 (independent) and 1 (same grouping)
+    pub fn normalized_mutual_information(&self) -> f64 {
+        let n = self.size as f64;
+        let entropy = |sizes: Vec<usize>| -> f64 {
+            sizes.into_iter().filter(|&s| s > 0).map(|s| s as f64 / n).map(|p| -p * p.ln()).sum()
+        };
+
+        let cluster_sizes = self.cluster_sizes();
+        let class_sizes = self.class_sizes();
+
+        let mut mutual_information = 0.0;
+        for (row, &cluster_size) in self.counts.iter().zip(cluster_sizes.iter()) {
+            for (&count, &class_size) in row.iter().zip(class_sizes.iter()).filter(|(&count, _)| count > 0) {
+                let count = count as f64;
+                mutual_information += count / n * (n * count / (cluster_size as f64 * class_size as f64)).ln();
+            }
+        }
+
+        let mean_entropy = (entropy(cluster_sizes) + entropy(class_sizes)) / 2.0;
+
+        // Both labelings are a single group: they are the same grouping
+        if mean_entropy == 0.0 {
+            1.0
+        }
+        else {
+            (mutual_information / mean_entropy).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Purity: fraction of the elements that belong to the most common class of their cluster
+    pub fn purity(&self) -> f64 {
+        let majority: usize = self.counts.iter().map(|row| row.iter().max().copied().unwrap_or(0)).sum();
+        majority as f64 / self.size as f64
+    }
+}
+
+/// Cluster-validity indices of a partition, calculated from the data alone
+/// - silhouette: f64 - Mean silhouette, between -1 and 1 (higher is better)
+/// - davies_bouldin: f64 - Davies–Bouldin index (lower is better)
+/// - calinski_harabasz: f64 - Calinski–Harabasz index (higher is better)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InternalScores {
+    pub silhouette: f64,
+    pub davies_bouldin: f64,
+    pub calinski_harabasz: f64,
+}
+
+/// Agreement of a partition with the ground truth of its problem
+/// - adjusted_rand_index: f64 - Adjusted Rand index
+/// - normalized_mutual_information: f64 - Normalized mutual information
+/// - purity: f64 - Purity
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ExternalScores {
+    pub adjusted_rand_index: f64,
+    pub normalized_mutual_information: f64,
+    pub purity: f64,
+}
+
+impl Problem {
+    /// Scores the structure of a partition with the silhouette, Davies–Bouldin and
+    /// Calinski–Harabasz indices
+    /// - partition: &Partition - Partition to score
+    pub fn internal_scores(&self, partition: &Partition) -> InternalScores {
+        InternalScores {
+            silhouette: self.silhouette(partition.clusters()),
+            davies_bouldin: self.davies_bouldin(partition.clusters()),
+            calinski_harabasz: self.calinski_harabasz(partition.clusters()),
+        }
+    }
+
+    /// Scores a partition against the ground truth of the problem
+    /// - partition: &Partition - Partition to score
+    /// #### Return value
+    /// Option<ExternalScores> Scores of the partition, or `None` if the ground truth is not known
+    pub fn external_scores(&self, partition: &Partition) -> Option<ExternalScores> {
+        let contingency = Contingency::new(partition, self.ground_truth()?);
+
+        Some(ExternalScores {
+            adjusted_rand_index: contingency.adjusted_rand_index(),
+            normalized_mutual_information: contingency.normalized_mutual_information(),
+            purity: contingency.purity(),
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::testing::assert_close;
+
+    #[test]
+    fn contingency_counts_clusters_and_classes() {
+        let table = Contingency::from_labels(&[0, 0, 0, 1, 1, 1], &[0, 0, 1, 1, 2, 2]);
+
+        assert_eq!(table.counts(), &[vec![2, 1, 0], vec![0, 1, 2]]);
+        assert_eq!(table.cluster_sizes(), vec![3, 3]);
+        assert_eq!(table.class_sizes(), vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn external_scores_of_a_partial_match() {
+        let table = Contingency::from_labels(&[0, 0, 0, 1, 1, 1], &[0, 0, 1, 1, 2, 2]);
+
+        // Pairs: 2 together in both, 6 in the same cluster, 3 in the same class, 15 in total
+        // Expected index 6·3/15 = 1.2 and maximum (6 + 3)/2 = 4.5
+        assert_close(table.adjusted_rand_index(), (2.0 - 1.2) / (4.5 - 1.2));
+        // Mutual information 2/3·ln 2, entropies ln 2 and ln 3
+        assert_close(table.normalized_mutual_information(), (2.0 / 3.0 * 2f64.ln()) / ((2f64.ln() + 3f64.ln()) / 2.0));
+        assert_close(table.purity(), 4.0 / 6.0);
+    }
+
+    #[test]
+    fn external_scores_of_a_perfect_match_with_other_names() {
+        let table = Contingency::from_labels(&[1, 1, 0, 0, 2], &[0, 0, 1, 1, 2]);
+
+        assert_close(table.adjusted_rand_index(), 1.0);
+        assert_close(table.normalized_mutual_information(), 1.0);
+        assert_close(table.purity(), 1.0);
+    }
+
+    #[test]
+    fn external_scores_of_independent_groupings() {
+        let table = Contingency::from_labels(&[0, 0, 1, 1], &[0, 1, 0, 1]);
+
+        // No pair together in both, 2 in the same cluster and 2 in the same class, out of 6
+        assert_close(table.adjusted_rand_index(), (0.0 - 4.0 / 6.0) / (2.0 - 4.0 / 6.0));
+        assert_close(table.normalized_mutual_information(), 0.0);
+        assert_close(table.purity(), 0.5);
+    }
+
+    #[test]
+    fn external_scores_of_single_groups() {
+        let table = Contingency::from_labels(&[0, 0, 0], &[0, 0, 0]);
+
+        assert_close(table.adjusted_rand_index(), 1.0);
+        assert_close(table.normalized_mutual_information(), 1.0);
+        assert_close(table.purity(), 1.0);
+    }
+}
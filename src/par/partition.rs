@@ -1,7 +1,8 @@
 use super::Problem;
-use std::collections::{HashSet, BTreeMap};
+use std::collections::{BTreeSet, BTreeMap};
 use std::fmt;
 use rand::Rng;
+use rand::seq::SliceRandom;
 use na::DVector;
 use rand_pcg::Pcg64;
 
@@ -20,13 +21,13 @@ pub struct Partition {
 
 
 impl Partition {
-    /// Creates a new empty Partition with random centroids for each cluster
-    /// - k: usize - Number of clusters in the partition
-    /// - dim: usize - Dimension of a point in the problem
-    pub fn new(k: usize, dim: usize, rng: &mut Pcg64) -> Partition {
+    /// Creates a new empty Partition with a random centroid for each cluster of the problem, drawn
+    /// within the bounding box of its data
+    /// - problem: &Problem - Instance of the problem
+    pub fn new(problem: &Problem, rng: &mut Pcg64) -> Partition {
         let mut clu = Vec::new();
-        for _ in 0..k { 
-            clu.push(Cluster::new_rand(dim, rng));
+        for _ in 0..problem.k() { 
+            clu.push(Cluster::new_rand(problem, rng));
         }
 
         Partition {
@@ -35,14 +36,74 @@ impl Partition {
         }
     }
 
+    /// Creates a new empty Partition with the given centroids, one per cluster
+    /// - problem: &Problem - Instance of the problem
+    /// - centroids: Vec<Point> - Centroid of each cluster
+    pub fn from_centroids(problem: &Problem, centroids: Vec<Point>) -> Partition {
+        let clu = centroids.into_iter().map(|centroid| {
+            let mut cluster = Cluster::new(problem.dimension());
+            cluster.set_centroid(centroid);
+            cluster
+        }).collect();
+
+        Partition {
+            cluster_index: BTreeMap::new(),
+            clusters: clu
+        }
+    }
+
+    /// Creates a random valid Partition: every element is assigned to a random cluster, and every
+    /// cluster receives at least one element
+    /// - problem: &Problem - Instance of the problem
+    pub fn new_rand(problem: &Problem, rng: &mut Pcg64) -> Partition {
+        let mut elements: Vec<usize> = (0..problem.size()).collect();
+        elements.shuffle(rng);
+
+        // The first k shuffled elements guarantee that no cluster is empty
+        let mut labels = vec![0; problem.size()];
+        for (i, &element) in elements.iter().enumerate() {
+            labels[element] = if i < problem.k() { i } else { rng.gen_range(0..problem.k()) };
+        }
+
+        Partition::from_labels(&labels, problem)
+    }
+
+    /// Creates a Partition from a label vector, computing the centroid of every non-empty cluster
+    /// - labels: &[usize] - Cluster assigned to each element, indexed by element
+    /// - problem: &Problem - Instance of the problem (needed to calculate the centroids)
+    pub fn from_labels(labels: &[usize], problem: &Problem) -> Partition {
+        let mut clu: Vec<Cluster> = (0..problem.k()).map(|_| Cluster::new(problem.dimension())).collect();
+        let mut index = BTreeMap::new();
+
+        for (element, &cluster) in labels.iter().enumerate() {
+            clu[cluster].insert(element, problem);
+            index.insert(element, cluster);
+        }
+
+        for cluster in clu.iter_mut().filter(|c| !c.is_empty()) {
+            let centroid = problem.calc_centroid(cluster);
+            cluster.set_centroid(centroid);
+        }
+
+        Partition {
+            cluster_index: index,
+            clusters: clu
+        }
+    }
+
+    /// Returns the label vector of the partition: the cluster of each element, in element order
+    pub fn labels(&self) -> Vec<usize> {
+        self.cluster_index.values().cloned().collect()
+    }
+
     /// Insert an element into a cluster
     /// - element: usize - Index of element to insert
     /// - cluster: usize - Index of cluster 
     /// - problem: &Problem - Instance of the problem (needed to calculate new cluster centroid)
     pub fn insert(&mut self, element: usize, cluster: usize, problem: &Problem) {
         // If the element is in another cluster, remove it
-        if self.cluster_index.contains_key(&element) {
-            self.clusters[cluster].remove(element);
+        if let Some(&current) = self.cluster_index.get(&element) {
+            self.clusters[current].remove(element);
         }
         
         // Insert in the new cluster and update the index
@@ -55,9 +116,12 @@ impl Partition {
     /// - cluster: usize - Index of cluster 
     /// - problem: &Problem - Instance of the problem (needed to calculate new cluster centroid)
     pub fn insert_and_update(&mut self, element: usize, cluster: usize, problem: &Problem) {
-        // If the element is in another cluster, remove it
-        if self.cluster_index.contains_key(&element) {
-            self.clusters[cluster].remove(element);
+        // If the element is in another cluster, remove it and update the centroid of that cluster
+        if let Some(&current) = self.cluster_index.get(&element) {
+            if current != cluster && self.clusters[current].remove(element) && !self.clusters[current].is_empty() {
+                let centroid = problem.calc_centroid(&self.clusters[current]);
+                self.clusters[current].set_centroid(centroid);
+            }
         }
         
         // Insert in the new cluster and update the index
@@ -66,19 +130,29 @@ impl Partition {
     }
     
 
-    /// Generate a neighbour by changing `element` to `cluster`
+    /// Returns `true` if `element` can leave its current cluster without leaving it empty
+    /// - element: usize - Index of element to check
+    ///
+    /// An element that is not assigned to any cluster can always be moved
+    pub fn can_move(&self, element: usize) -> bool {
+        match self.cluster_index.get(&element) {
+            Some(&current) => self.clusters[current].len() > 1,
+            None => true,
+        }
+    }
+
+    /// Generate a neighbour by changing `element` to `cluster`, updating the affected centroids
     /// #### Return value:
     /// - `Some(neighbour)` where neighbour is valid
-    /// - `None` if the neighbour is not valid
+    /// - `None` if the neighbour is not valid (the element would leave its cluster empty)
     pub fn gen_neighbour(&self, element: usize, cluster: usize, problem: &Problem) -> Option<Partition> {
-        let mut neighbour = self.clone();
-        neighbour.insert(element, cluster, problem);
-        
         // Check if valid
-        if neighbour.get_cluster(cluster).is_empty() {
+        if !self.can_move(element) {
             None
         }
         else {
+            let mut neighbour = self.clone();
+            neighbour.insert_and_update(element, cluster, problem);
             Some(neighbour)
         }
     }
@@ -107,6 +181,7 @@ impl Partition {
 
     /// Get value of cluster index by a key
     /// - element: usize - Element to check
+    ///
     /// Returns *None* if not in the index
     pub fn get_cluster_index_for(&self, element: usize) -> Option<&usize> {
         self.cluster_index.get(&element)
@@ -117,12 +192,12 @@ impl Partition {
 /// Representation of a cluster on a PAR problem
 /// ## Elements
 /// - centroid: Point - Current centroid
-/// - elements: HashSet<usize> - Set of element indexes (not Points)
+/// - elements: BTreeSet<usize> - Set of element indexes (not Points)
 /// - dimension: usize - Dimension of the problem Points
 #[derive(Clone)]
 pub struct Cluster {
     centroid: Point,
-    elements: HashSet<usize>,
+    elements: BTreeSet<usize>,
     dimension: usize,
 }
 
@@ -133,28 +208,28 @@ impl Cluster {
     pub fn new(dim: usize) -> Cluster {
         Cluster {
             centroid: Point::zeros(dim),
-            elements: HashSet::new(),
+            elements: BTreeSet::new(),
             dimension: dim,
         }
     }
 
     /// Creates a new empty cluster with a randomized centroid
     /// # Arguments
-    /// - dim: usize - Dimension of points in the problem
-    pub fn new_rand(dim: usize, rng: &mut Pcg64) -> Cluster {
-        let mut new_cluster = Cluster {
-            centroid: Point::zeros(dim),
-            elements: HashSet::new(),
-            dimension: dim,
-        };
-        new_cluster.randomize_centroid(rng);
+    /// - problem: &Problem - Instance of the problem, whose data bounds the centroid
+    pub fn new_rand(problem: &Problem, rng: &mut Pcg64) -> Cluster {
+        let mut new_cluster = Cluster::new(problem.dimension());
+        new_cluster.randomize_centroid(problem, rng);
         
         new_cluster
     }
 
-    /// Randomizes the centroid
-    pub fn randomize_centroid(&mut self, rng: &mut Pcg64) {
-        let random_centroid: Vec<f64> = (0..self.dimension).map(|_| rng.gen()).collect(); 
+    /// Randomizes the centroid, drawing each coordinate uniformly between the lowest and highest
+    /// value of that feature in the data
+    /// # Arguments
+    /// - problem: &Problem - Instance of the problem, whose data bounds the centroid
+    pub fn randomize_centroid(&mut self, problem: &Problem, rng: &mut Pcg64) {
+        let (lower, upper) = problem.bounds();
+        let random_centroid: Vec<f64> = (0..self.dimension).map(|i| lower[i] + rng.gen::<f64>() * (upper[i] - lower[i])).collect(); 
         self.centroid = Point::from(random_centroid);
     }
 
@@ -168,7 +243,7 @@ impl Cluster {
     /// Inserts a new element into the cluster
     /// # Arguments
     /// - e: usize - Index of an element
-    pub fn insert(&mut self, e: usize, problem: &Problem) -> bool {
+    pub fn insert(&mut self, e: usize, _problem: &Problem) -> bool {
         self.elements.insert(e)
     }
 
@@ -200,7 +275,7 @@ impl Cluster {
     }
 
     /// Returns a reference to the elements set
-    pub fn elements(&self) -> &HashSet<usize> {
+    pub fn elements(&self) -> &BTreeSet<usize> {
         &self.elements
     }
 
@@ -214,6 +289,11 @@ impl Cluster {
         self.dimension
     }
 
+    /// Returns the number of elements in the cluster
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
     /// Returns `true` if cluster is empty
     pub fn is_empty(&self) -> bool {
         self.elements.is_empty()
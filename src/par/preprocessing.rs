@@ -0,0 +1,252 @@
+use super::Point;
+use std::fmt::{Display, Formatter, Result};
+
+
+/// Scaling of each feature of the data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scaling {
+    /// Features are used as they are
+    #[default]
+    None,
+    /// Features are mapped to [0, 1]
+    MinMax,
+    /// Features are centred on their mean and divided by their standard deviation
+    ZScore,
+    /// Features are centred on their median and divided by their interquartile range, so that
+    /// outliers do not decide the scale
+    Robust,
+}
+
+impl Scaling {
+    /// Names of the scalings, as accepted by `Scaling::from_name`
+    pub const NAMES: [&'static str; 4] = ["none", "min-max", "z-score", "robust"];
+
+    /// Returns the scaling with the given name, or `None` if there is no scaling with that name
+    pub fn from_name(name: &str) -> Option<Scaling> {
+        match name {
+            "none" => Some(Scaling::None),
+            "min-max" => Some(Scaling::MinMax),
+            "z-score" => Some(Scaling::ZScore),
+            "robust" => Some(Scaling::Robust),
+            _ => None,
+        }
+    }
+
+    /// Name of the scaling
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scaling::None => "none",
+            Scaling::MinMax => "min-max",
+            Scaling::ZScore => "z-score",
+            Scaling::Robust => "robust",
+        }
+    }
+
+    /// Offset and scale of a feature, such that its scaled values are `(x - offset) / scale`
+    /// Features without spread keep a scale of 1, so that they are not divided by 0
+    /// - values: &[f64] - Values of the feature, sorted
+    fn fit(&self, values: &[f64]) -> (f64, f64) {
+        let (offset, scale) = match self {
+            Scaling::None => (0.0, 1.0),
+            Scaling::MinMax => (values[0], values[values.len() - 1] - values[0]),
+            Scaling::ZScore => {
+                let n = values.len() as f64;
+                let mean = values.iter().sum::<f64>() / n;
+                let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+                (mean, variance.sqrt())
+            },
+            Scaling::Robust => (quantile(values, 0.5), quantile(values, 0.75) - quantile(values, 0.25)),
+        };
+
+        (offset, if scale > 0.0 { scale } else { 1.0 })
+    }
+}
+
+impl Display for Scaling {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Quantile of sorted values, interpolating linearly between the closest ones
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+
+/// Preprocessing applied to the data of a problem when it is loaded
+/// - scaling: Scaling - Scaling of each feature
+/// - drop_constant: bool - Whether features with the same value in every point are removed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Preprocessing {
+    pub scaling: Scaling,
+    pub drop_constant: bool,
+}
+
+/// Transform applied to the data of a problem, fitted on the data itself
+/// A point of the original data `x` becomes the point made of `(x[c] - offset) / scale` for each
+/// kept column `c`
+/// - scaling: Scaling - Scaling of each feature
+/// - dimension: usize - Dimension of the original data
+/// - columns: Vec<usize> - Columns of the original data that are kept, in order
+/// - offsets: Vec<f64> - Offset of each kept column
+/// - scales: Vec<f64> - Scale of each kept column
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub scaling: Scaling,
+    pub dimension: usize,
+    pub columns: Vec<usize>,
+    pub offsets: Vec<f64>,
+    pub scales: Vec<f64>,
+}
+
+impl Transform {
+    /// Transform that keeps the data as it is
+    /// - dimension: usize - Dimension of the points
+    pub fn identity(dimension: usize) -> Transform {
+        Transform {
+            scaling: Scaling::None,
+            dimension,
+            columns: (0..dimension).collect(),
+            offsets: vec![0.0; dimension],
+            scales: vec![1.0; dimension],
+        }
+    }
+
+    /// Fits a preprocessing on the data
+    /// - data: &[Point] - Points of the problem, which must not be empty
+    /// - preprocessing: &Preprocessing - Preprocessing to fit
+    /// #### Return value
+    /// Transform Fitted transform, which keeps every column if all of them are constant
+    pub fn fit(data: &[Point], preprocessing: &Preprocessing) -> Transform {
+        let dimension = data[0].len();
+        let mut transform = Transform {
+            scaling: preprocessing.scaling,
+            dimension,
+            columns: Vec::new(),
+            offsets: Vec::new(),
+            scales: Vec::new(),
+        };
+
+        let mut features = Vec::with_capacity(dimension);
+        for column in 0..dimension {
+            let mut values: Vec<f64> = data.iter().map(|p| p[column]).collect();
+            values.sort_by(f64::total_cmp);
+            features.push(values);
+        }
+
+        let constant = |values: &Vec<f64>| values[0] == values[values.len() - 1];
+        // Dropping every column would leave points without features
+        let drop_constant = preprocessing.drop_constant && !features.iter().all(constant);
+
+        for (column, values) in features.iter().enumerate() {
+            if drop_constant && constant(values) {
+                continue;
+            }
+
+            let (offset, scale) = preprocessing.scaling.fit(values);
+            transform.columns.push(column);
+            transform.offsets.push(offset);
+            transform.scales.push(scale);
+        }
+
+        transform
+    }
+
+    /// Applies the transform to a point of the original data
+    pub fn apply(&self, point: &Point) -> Point {
+        Point::from_iterator(self.columns.len(), self.columns.iter()
+            .zip(self.offsets.iter().zip(self.scales.iter()))
+            .map(|(&column, (offset, scale))| (point[column] - offset) / scale))
+    }
+
+    /// Columns of the original data that have been removed
+    pub fn dropped_columns(&self) -> Vec<usize> {
+        (0..self.dimension).filter(|c| !self.columns.contains(c)).collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::testing::assert_close;
+
+    /// Points whose first feature has an outlier, second one is constant and third one is small
+    fn data() -> Vec<Point> {
+        [[1.0, 5.0, 0.0], [2.0, 5.0, 1.0], [3.0, 5.0, 0.0], [4.0, 5.0, 1.0], [10.0, 5.0, 0.5]].iter()
+            .map(|p| Point::from_vec(p.to_vec()))
+            .collect()
+    }
+
+    fn fit(scaling: Scaling, drop_constant: bool) -> Transform {
+        Transform::fit(&data(), &Preprocessing { scaling, drop_constant })
+    }
+
+    #[test]
+    fn no_scaling_is_the_identity() {
+        assert_eq!(fit(Scaling::None, false), Transform::identity(3));
+    }
+
+    #[test]
+    fn min_max_maps_every_feature_to_the_unit_interval() {
+        let transform = fit(Scaling::MinMax, false);
+        assert_eq!(transform.offsets, vec![1.0, 5.0, 0.0]);
+        assert_eq!(transform.scales, vec![9.0, 1.0, 1.0]);
+
+        let scaled: Vec<Point> = data().iter().map(|p| transform.apply(p)).collect();
+        assert_close(scaled[0][0], 0.0);
+        assert_close(scaled[4][0], 1.0);
+        assert!(scaled.iter().all(|p| p[1] == 0.0));
+    }
+
+    #[test]
+    fn z_score_centres_on_the_mean_with_unit_deviation() {
+        let transform = fit(Scaling::ZScore, false);
+        assert_close(transform.offsets[0], 4.0);
+        assert_close(transform.scales[0], 10.0f64.sqrt());
+        // Constant features keep a scale of 1
+        assert_eq!(transform.scales[1], 1.0);
+
+        let column: Vec<f64> = data().iter().map(|p| transform.apply(p)[2]).collect();
+        let mean = column.iter().sum::<f64>() / 5.0;
+        let variance = column.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 5.0;
+        assert_close(mean, 0.0);
+        assert_close(variance, 1.0);
+    }
+
+    #[test]
+    fn robust_centres_on_the_median_and_divides_by_the_interquartile_range() {
+        let transform = fit(Scaling::Robust, false);
+        assert_eq!(transform.offsets, vec![3.0, 5.0, 0.5]);
+        assert_eq!(transform.scales, vec![2.0, 1.0, 1.0]);
+
+        // Quantiles between two values are interpolated
+        assert_close(quantile(&[1.0, 2.0, 3.0, 4.0], 0.25), 1.75);
+        assert_close(quantile(&[1.0, 2.0, 3.0, 4.0], 0.5), 2.5);
+    }
+
+    #[test]
+    fn drop_constant_removes_the_constant_features() {
+        let transform = fit(Scaling::MinMax, true);
+        assert_eq!(transform.columns, vec![0, 2]);
+        assert_eq!(transform.dropped_columns(), vec![1]);
+
+        let scaled = transform.apply(&data()[1]);
+        assert_eq!(scaled.len(), 2);
+        assert_close(scaled[0], 1.0 / 9.0);
+        assert_close(scaled[1], 1.0);
+    }
+
+    #[test]
+    fn drop_constant_keeps_every_feature_if_all_are_constant() {
+        let data = vec![Point::from_vec(vec![1.0, 2.0]); 3];
+        let transform = Transform::fit(&data, &Preprocessing { scaling: Scaling::ZScore, drop_constant: true });
+
+        assert_eq!(transform.columns, vec![0, 1]);
+        assert!(transform.dropped_columns().is_empty());
+    }
+}
@@ -1,110 +1,511 @@
 use na::DVector;
 use std::vec::Vec;
-use std::collections::{HashMap, BTreeMap};
+use std::collections::BTreeMap;
 use std::fs::*;
 use std::io::{BufReader, BufRead};
+use std::path::Path;
 use std::fmt::{Display, Formatter, Result};
-use colored::*;
-use super::{Partition, Cluster};
+use std::str::FromStr;
+use super::{Partition, Cluster, Metric, Preprocessing, Transform, ConstraintKind, ProblemError};
 
 // Custom types
 pub type Point = DVector<f64>;
 
 
-pub struct Problem {
-    data: Vec<Point>,
-    constraints: HashMap<(usize, usize), i8>,
-    k: usize,
-    lambda: f64,
+/// Format of a constraints file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstraintFormat {
+    /// Symmetric n×n matrix of comma-separated values: positive for must-links, negative for
+    /// cannot-links and 0 for no constraint
+    Matrix,
+    /// One `i,j,value` (or whitespace-separated `i j value`) constraint per line, with 0-based
+    /// indexes and a positive value for a must-link or a negative one for a cannot-link. Each pair
+    /// of different elements can only appear once
+    List,
 }
 
-impl Problem {
-    /// Creates a new Problem with data from two files
-    /// # Arguments
-    /// - data_file: &str - Path to a data file
-    /// - constraints_file: &str - Path to a constraint file
-    pub fn from_files(data_file: &str, constraints_file: &str, cl_number: usize) -> Problem {
-        // Attributes
-        let mut points = Vec::new();
-        let mut cons = HashMap::new();
+impl ConstraintFormat {
+    /// Detects the format of a constraints file
+    /// Files with a `.list` extension are lists. Otherwise, the first line decides: a list line
+    /// has three fields, while a matrix row has as many comma-separated fields as elements in the
+    /// problem (so three whitespace-separated fields are always a list)
+    /// - path: &str - Path to the constraints file
+    /// - first_line: &str - First line of the file
+    /// - size: usize - Number of elements in the problem
+    pub fn detect(path: &str, first_line: &str, size: usize) -> ConstraintFormat {
+        let fields = list_fields(first_line).len();
+        let comma_separated = first_line.contains(',');
+
+        if Path::new(path).extension().is_some_and(|e| e == "list") || (fields == 3 && (size != 3 || !comma_separated)) {
+            ConstraintFormat::List
+        }
+        else {
+            ConstraintFormat::Matrix
+        }
+    }
+}
 
-        // Open data file
-        let data = File::open(data_file).expect("Data file not found");
-        let reader = BufReader::new(data);
+impl Display for ConstraintFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ConstraintFormat::Matrix => write!(f, "matrix"),
+            ConstraintFormat::List => write!(f, "list"),
+        }
+    }
+}
 
-        // Each line in the data file represents a Point in the problem space
-        print!("Reading data from {}: ", data_file);
-        for line in reader.lines() {
-            let p = line.unwrap();
-            
-            if !p.is_empty() {
-                let p: Vec<f64> = p.split(",").map(|i| i.parse().unwrap()).collect();
-                points.push(Point::from(p));
+/// Constraint lists being built while reading a constraints file, with the weight of each constraint
+struct ConstraintLists {
+    must_link: Vec<Vec<(usize, f64)>>,
+    cannot_link: Vec<Vec<(usize, f64)>>,
+    number: usize,
+}
+
+impl ConstraintLists {
+    fn new(size: usize) -> ConstraintLists {
+        ConstraintLists {
+            must_link: vec![Vec::new(); size],
+            cannot_link: vec![Vec::new(); size],
+            number: 0,
+        }
+    }
+
+    /// Adds a constraint between two different elements, ignoring zeros
+    /// The sign of the value is the kind of the constraint, and its absolute value the weight
+    fn add(&mut self, a: usize, b: usize, value: f64) {
+        let lists = if value > 0.0 {
+            &mut self.must_link
+        }
+        else if value < 0.0 {
+            &mut self.cannot_link
+        }
+        else {
+            return;
+        };
+
+        if a != b {
+            lists[a].push((b, value.abs()));
+            lists[b].push((a, value.abs()));
+            self.number += 1;
+        }
+    }
+}
+
+
+/// Reads the non-empty lines of a file, with their 1-based line numbers
+fn read_lines(file: &str) -> std::result::Result<Vec<(usize, String)>, ProblemError> {
+    let io_error = |source| ProblemError::Io { file: file.to_string(), source };
+    let reader = BufReader::new(File::open(file).map_err(io_error)?);
+
+    let mut lines = Vec::new();
+    for (ln, line) in reader.lines().enumerate() {
+        let line = line.map_err(io_error)?;
+        if !line.trim().is_empty() {
+            lines.push((ln + 1, line));
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Splits a line of a constraint list into its fields, separated by commas or, if there are no
+/// commas, by whitespace
+fn list_fields(line: &str) -> Vec<&str> {
+    if line.contains(',') {
+        line.split(',').collect()
+    }
+    else {
+        line.split_whitespace().collect()
+    }
+}
+
+/// Parses a field of a line
+fn parse_field<T: FromStr>(file: &str, line: usize, column: usize, value: &str) -> std::result::Result<T, ProblemError> {
+    value.trim().parse().map_err(|_| ProblemError::Parse {
+        file: file.to_string(),
+        line,
+        column,
+        value: value.to_string(),
+    })
+}
+
+/// Reads a data file, where each line is a comma-separated point
+fn read_data(file: &str) -> std::result::Result<Vec<Point>, ProblemError> {
+    let mut points: Vec<Point> = Vec::new();
+
+    for (line, content) in read_lines(file)? {
+        let p = content.split(",")
+            .enumerate()
+            .map(|(column, value)| parse_field(file, line, column + 1, value))
+            .collect::<std::result::Result<Vec<f64>, _>>()?;
+
+        if let Some(column) = p.iter().position(|value| !value.is_finite()) {
+            return Err(ProblemError::InvalidCoordinate { file: file.to_string(), line, column: column + 1, value: p[column] });
+        }
+
+        // Every point must have the dimension of the first one
+        if let Some(first) = points.first() {
+            if first.len() != p.len() {
+                return Err(ProblemError::RaggedRow { file: file.to_string(), line, expected: first.len(), found: p.len() });
             }
         }
 
-        // Read data OK
-        print!("{}\n", "OK".green().bold());
+        points.push(Point::from(p));
+    }
 
-        // Open constraints file
-        let constraints = File::open(constraints_file).expect("Constraints file not found");
-        let reader = BufReader::new(constraints);
+    if points.is_empty() {
+        return Err(ProblemError::EmptyData { file: file.to_string() });
+    }
+
+    Ok(points)
+}
+
+/// Reads a constraints file, detecting its format
+fn read_constraints(file: &str, size: usize) -> std::result::Result<ConstraintLists, ProblemError> {
+    let lines = read_lines(file)?;
+    let first_line = lines.first().map_or("", |(_, content)| content.as_str());
 
-        // The constraints file represents the constraint matrix
-        print!("Reading constraints from {}: ", constraints_file);
+    let mut constraints = ConstraintLists::new(size);
+    let invalid = |line, column, value| ProblemError::InvalidConstraint { file: file.to_string(), line, column, value };
 
-        let mut constraint_number = 0;
+    match ConstraintFormat::detect(file, first_line, size) {
+        ConstraintFormat::Matrix => {
+            if lines.len() != size {
+                return Err(ProblemError::Dimension { file: file.to_string(), expected: size, found: lines.len() });
+            }
+
+            // Constraints of the current row already read from previous rows
+            let mut expected = vec![0.0; size];
 
-        for (ln, line) in reader.lines().enumerate() {
-            let c = line.unwrap();
+            for (row, (line, content)) in lines.iter().enumerate() {
+                let values = content.split(",")
+                    .enumerate()
+                    .map(|(column, value)| parse_field::<f64>(file, *line, column + 1, value))
+                    .collect::<std::result::Result<Vec<f64>, _>>()?;
 
-            if !c.is_empty() {
-                for (i, val) in c.split(",").map(|x| x.parse::<i8>().unwrap()).enumerate().skip(ln) {
-                    cons.insert((ln, i), val);
-                    constraint_number += 1;
+                if values.len() != size {
+                    return Err(ProblemError::RaggedRow { file: file.to_string(), line: *line, expected: size, found: values.len() });
+                }
 
-                    // Symmetric matrix
-                    if ln != i {
-                        cons.insert((i, ln), val);
+                for &(other, weight) in constraints.must_link[row].iter() {
+                    expected[other] = weight;
+                }
+                for &(other, weight) in constraints.cannot_link[row].iter() {
+                    expected[other] = -weight;
+                }
+
+                for (column, &value) in values.iter().enumerate() {
+                    if !value.is_finite() {
+                        return Err(invalid(*line, column + 1, value));
+                    }
+
+                    // Symmetric matrix: the lower triangle must mirror the upper one, without the diagonal
+                    if column < row && value != expected[column] {
+                        return Err(ProblemError::Asymmetric { file: file.to_string(), line: *line, column: column + 1 });
                     }
+                    else if column > row {
+                        constraints.add(row, column, value);
+                    }
+                }
+
+                for e in expected.iter_mut().take(row) {
+                    *e = 0.0;
                 }
             }
-        }
+        },
+        ConstraintFormat::List => {
+            // Line and value of the constraint of each pair already read
+            let mut read: BTreeMap<(usize, usize), (usize, f64)> = BTreeMap::new();
+
+            for (line, content) in lines {
+                // Each line is a pair of elements and the constraint value
+                let fields = list_fields(&content);
+                if fields.len() != 3 {
+                    return Err(ProblemError::RaggedRow { file: file.to_string(), line, expected: 3, found: fields.len() });
+                }
 
-        // Read constraints OK
-        print!("{}\n", "OK".green().bold());
+                let a: usize = parse_field(file, line, 1, fields[0])?;
+                let b: usize = parse_field(file, line, 2, fields[1])?;
+                let value: f64 = parse_field(file, line, 3, fields[2])?;
 
-        // Calculate and store distance between points
-        let mut dist = HashMap::new();
-        for (i, e1) in points.iter().enumerate() {
-            for (j, e2) in points.iter().enumerate() {
-                dist.insert((i, j), e1.metric_distance(e2));
+                for (column, &index) in [a, b].iter().enumerate() {
+                    if index >= size {
+                        return Err(ProblemError::IndexOutOfRange { file: file.to_string(), line, column: column + 1, index, size });
+                    }
+                }
+                if value == 0.0 || !value.is_finite() {
+                    return Err(invalid(line, 3, value));
+                }
+                if a == b {
+                    return Err(ProblemError::SelfConstraint { file: file.to_string(), line, element: a });
+                }
 
-                if i != j {
-                    dist.insert((j, i), *dist.get(&(i, j)).unwrap());
+                if let Some(&(first, previous)) = read.get(&(a.min(b), a.max(b))) {
+                    let pair = (a, b);
+                    return Err(if (previous > 0.0) == (value > 0.0) {
+                        ProblemError::DuplicateConstraint { file: file.to_string(), line, first, pair }
+                    }
+                    else {
+                        ProblemError::ContradictoryConstraint { file: file.to_string(), line, first, pair }
+                    });
                 }
+
+                read.insert((a.min(b), a.max(b)), (line, value));
+                constraints.add(a, b, value);
             }
+        },
+    }
+
+    Ok(constraints)
+}
+
+
+/// Reads a ground-truth file, numbering its classes in order of appearance
+fn read_ground_truth(file: &str, size: usize) -> std::result::Result<Vec<usize>, ProblemError> {
+    let mut names: Vec<String> = Vec::new();
+    let mut classes = Vec::with_capacity(size);
+
+    for (_, line) in read_lines(file)?.iter().filter(|(_, line)| !line.trim_start().starts_with('#')) {
+        for name in line.split(|c: char| c == ',' || c.is_whitespace()).filter(|n| !n.is_empty()) {
+            let class = match names.iter().position(|n| n == name) {
+                Some(class) => class,
+                None => {
+                    names.push(name.to_string());
+                    names.len() - 1
+                },
+            };
+            classes.push(class);
         }
+    }
 
-        // Calculate lambda as (max_distance + n) / |constraints|
-        let lmbd = dist.values().cloned().fold(0./0., f64::max) / constraint_number as f64;
+    if classes.len() != size {
+        return Err(ProblemError::Labels { file: file.to_string(), expected: size, found: classes.len() });
+    }
+
+    Ok(classes)
+}
+
+/// Calculates the weight of the infeasibility: the maximum distance between two points divided by
+/// the number of constraints, so that a single violated constraint outweighs any distance
+fn calc_lambda(points: &[Point], constraint_number: usize, metric: &Metric) -> f64 {
+    let mut max_distance: f64 = 0.0;
+    for (i, e1) in points.iter().enumerate() {
+        for e2 in points.iter().skip(i + 1) {
+            max_distance = max_distance.max(metric.distance(e1, e2));
+        }
+    }
+
+    max_distance / constraint_number.max(1) as f64
+}
+
+
+/// Instance of a PAR problem
+/// - data: Vec<Point> - Points of the problem
+/// - must_link: Vec<Vec<(usize, f64)>> - Elements that must be in the same cluster as each element, with the weight of the constraint
+/// - cannot_link: Vec<Vec<(usize, f64)>> - Elements that cannot be in the same cluster as each element, with the weight of the constraint
+/// - constraint_number: usize - Number of constraints (each pair is counted once)
+/// - penalties: (f64, f64) - Multipliers of the weights of violated must-links and cannot-links
+/// - k: usize - Number of clusters
+/// - lambda: f64 - Weight of the infeasibility in the fitness
+/// - metric: Metric - Distance between points and centroids
+/// - transform: Transform - Preprocessing applied to the data of the files
+/// - bounds: (Point, Point) - Lowest and highest value of each feature of the data
+/// - ground_truth: Option<Vec<usize>> - Class of each element, if it is known
+pub struct Problem {
+    data: Vec<Point>,
+    must_link: Vec<Vec<(usize, f64)>>,
+    cannot_link: Vec<Vec<(usize, f64)>>,
+    constraint_number: usize,
+    penalties: (f64, f64),
+    k: usize,
+    lambda: f64,
+    metric: Metric,
+    transform: Transform,
+    bounds: (Point, Point),
+    ground_truth: Option<Vec<usize>>,
+}
+
+impl Problem {
+    /// Creates a new Problem with data from two files
+    /// Panics if the files cannot be loaded (see `Problem::load`)
+    /// # Arguments
+    /// - data_file: &str - Path to a data file
+    /// - constraints_file: &str - Path to a constraint file, either a matrix or a list (see `ConstraintFormat`)
+    pub fn from_files(data_file: &str, constraints_file: &str, cl_number: usize) -> Problem {
+        Problem::load(data_file, constraints_file, cl_number).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new Problem with data from two files, checking that they are well formed
+    /// # Arguments
+    /// - data_file: &str - Path to a data file
+    /// - constraints_file: &str - Path to a constraint file, either a matrix or a list (see `ConstraintFormat`)
+    /// - cl_number: usize - Number of clusters
+    pub fn load(data_file: &str, constraints_file: &str, cl_number: usize) -> std::result::Result<Problem, ProblemError> {
+        Problem::load_preprocessed(data_file, constraints_file, cl_number, &Preprocessing::default())
+    }
+
+    /// Creates a new Problem with data from two files, preprocessing the data before using it
+    /// # Arguments
+    /// - data_file: &str - Path to a data file
+    /// - constraints_file: &str - Path to a constraint file, either a matrix or a list (see `ConstraintFormat`)
+    /// - cl_number: usize - Number of clusters
+    /// - preprocessing: &Preprocessing - Scaling and removal of constant features, fitted on the data
+    pub fn load_preprocessed(data_file: &str, constraints_file: &str, cl_number: usize, preprocessing: &Preprocessing) -> std::result::Result<Problem, ProblemError> {
+        // Each line in the data file represents a Point in the problem space
+        let points = read_data(data_file)?;
+
+        let transform = Transform::fit(&points, preprocessing);
+        let points: Vec<Point> = points.iter().map(|p| transform.apply(p)).collect();
+
+        if cl_number == 0 || cl_number > points.len() {
+            return Err(ProblemError::Clusters { k: cl_number, size: points.len() });
+        }
+
+        // The constraints file represents the constraint matrix or a constraint list
+        let constraints = read_constraints(constraints_file, points.len())?;
+
+        Ok(Problem::new(points, constraints, cl_number, transform))
+    }
+
+    /// Creates a Problem from its (already preprocessed) points and constraints
+    fn new(points: Vec<Point>, constraints: ConstraintLists, cl_number: usize, transform: Transform) -> Problem {
+        let ConstraintLists { must_link, cannot_link, number: constraint_number } = constraints;
+
+        // Calculate lambda as max_distance / |constraints|
+        let metric = Metric::default();
+        let lmbd = calc_lambda(&points, constraint_number, &metric);
+
+        // Bounding box of the data, where random centroids are drawn
+        let lower = Point::from_fn(points[0].len(), |i, _| points.iter().map(|p| p[i]).fold(f64::INFINITY, f64::min));
+        let upper = Point::from_fn(points[0].len(), |i, _| points.iter().map(|p| p[i]).fold(f64::NEG_INFINITY, f64::max));
 
         // Returns a Problem
         Problem {
             data: points,
-            constraints: cons,
+            must_link,
+            cannot_link,
+            constraint_number,
+            penalties: (1.0, 1.0),
             k: cl_number,
             lambda: lmbd,
+            metric,
+            transform,
+            bounds: (lower, upper),
+            ground_truth: None,
+        }
+    }
+
+    /// Creates a Problem without files, for tests
+    /// - points: Vec<Point> - Points of the problem
+    /// - constraints: &[(usize, usize, f64)] - Constraints as `(i, j, value)`, like the lines of a list file
+    /// - cl_number: usize - Number of clusters
+    #[cfg(test)]
+    pub(crate) fn from_points(points: Vec<Point>, constraints: &[(usize, usize, f64)], cl_number: usize) -> Problem {
+        let mut lists = ConstraintLists::new(points.len());
+        for &(a, b, value) in constraints {
+            lists.add(a, b, value);
+        }
+        let transform = Transform::identity(points[0].len());
+
+        Problem::new(points, lists, cl_number, transform)
+    }
+
+    /// Sets the distance metric of the problem, recalculating lambda with it
+    /// - metric: Metric - New distance metric
+    pub fn set_metric(&mut self, metric: Metric) {
+        self.lambda = calc_lambda(&self.data, self.constraint_number, &metric);
+        self.metric = metric;
+    }
+
+    /// Sets the distance metric of the problem from its name (see `Metric::NAMES`)
+    /// - name: &str - Name of the metric
+    pub fn set_metric_name(&mut self, name: &str) -> std::result::Result<(), ProblemError> {
+        let metric = Metric::from_name(name, &self.data).ok_or_else(|| ProblemError::Metric { name: name.to_string() })?;
+        self.set_metric(metric);
+
+        Ok(())
+    }
+
+    /// Sets the multipliers of the weights of violated constraints, 1 for both by default
+    /// - must_link: f64 - Multiplier of the must-links
+    /// - cannot_link: f64 - Multiplier of the cannot-links
+    pub fn set_penalties(&mut self, must_link: f64, cannot_link: f64) -> std::result::Result<(), ProblemError> {
+        for value in [must_link, cannot_link] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(ProblemError::Penalty { value });
+            }
         }
+        self.penalties = (must_link, cannot_link);
+
+        Ok(())
+    }
+
+    /// Returns the multipliers of the weights of violated must-links and cannot-links
+    pub fn penalties(&self) -> (f64, f64) {
+        self.penalties
+    }
+
+    /// Returns the preprocessing applied to the data of the files
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// Returns the lowest and highest value of each feature of the data
+    pub fn bounds(&self) -> (&Point, &Point) {
+        (&self.bounds.0, &self.bounds.1)
+    }
+
+    /// Returns the distance metric of the problem
+    pub fn metric(&self) -> &Metric {
+        &self.metric
+    }
+
+    /// Returns the distance between two points with the metric of the problem
+    pub fn distance(&self, a: &Point, b: &Point) -> f64 {
+        self.metric.distance(a, b)
+    }
+
+    /// Reads the class of each element from a ground-truth file, replacing the current one
+    /// Classes are separated by commas, spaces or new lines, and can be any word: they are numbered
+    /// in order of appearance. Lines starting with `#` are ignored
+    /// - labels_file: &str - Path to a ground-truth file, with one class per data point
+    pub fn load_ground_truth(&mut self, labels_file: &str) -> std::result::Result<(), ProblemError> {
+        let classes = read_ground_truth(labels_file, self.size())?;
+        self.ground_truth = Some(classes);
+
+        Ok(())
+    }
+
+    /// Sets the class of each element
+    /// Panics if there is not a class for each element
+    pub fn set_ground_truth(&mut self, classes: Vec<usize>) {
+        assert_eq!(classes.len(), self.size(), "There must be a class for each element");
+        self.ground_truth = Some(classes);
+    }
+
+    /// Returns the class of each element, if it is known
+    pub fn ground_truth(&self) -> Option<&[usize]> {
+        self.ground_truth.as_deref()
     }
 
     /// Returns a point given an index
     /// - index: usize - Index of the data vector
+    ///
     /// Returns an immutable reference to a `Point`
     pub fn data(&self, index: usize) -> &Point {
         &self.data[index]
     }
 
+    /// Returns the number of elements in the problem
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the dimension of the points in the problem
+    pub fn dimension(&self) -> usize {
+        self.data[0].len()
+    }
+
     /// Returns k
     pub fn k(&self) -> usize {
         self.k
@@ -127,39 +528,152 @@ impl Problem {
         // Accumulate distances
         let dist = cluster.elements()
             .iter()
-            .fold(0.0, |acc, &x| acc + cluster.centroid().metric_distance(self.data.get(x).unwrap()));
+            .fold(0.0, |acc, &x| acc + self.distance(cluster.centroid(), &self.data[x]));
 
         // Return mean
         dist / cluster.elements().len() as f64
     }
 
     /// Returns the general deviation of the current partition
-    pub fn general_deviation(&self, clusters: &Vec<Cluster>) -> f64 {
+    pub fn general_deviation(&self, clusters: &[Cluster]) -> f64 {
         // Accumulate distances
         let deviation = clusters.iter()
-            .fold(0.0, |acc, x| acc + self.intra_cluster_distance(&x));
+            .fold(0.0, |acc, x| acc + self.intra_cluster_distance(x));
 
         // Return mean
         deviation / self.k as f64
     }
 
-    /// Returns the infeasibility increment of inserting an element into a cluster 
+    /// Returns the mean silhouette of the elements of a partition, between -1 and 1 (higher is better)
+    /// The silhouette of an element compares its mean distance to the rest of its cluster with its
+    /// mean distance to the nearest other cluster. Elements alone in their cluster score 0
+    /// - clusters: &[Cluster] - Clusters of the partition
+    pub fn silhouette(&self, clusters: &[Cluster]) -> f64 {
+        let clusters: Vec<&Cluster> = clusters.iter().filter(|c| !c.is_empty()).collect();
+        if clusters.len() < 2 {
+            return 0.0;
+        }
+
+        let mean_distance = |element: usize, cluster: &Cluster| -> f64 {
+            let total: f64 = cluster.elements().iter().map(|&other| self.distance(&self.data[element], &self.data[other])).sum();
+            // An element is at distance 0 of itself, so it is not counted in its own cluster
+            let others = if cluster.contains(element) { cluster.len() - 1 } else { cluster.len() };
+            total / others as f64
+        };
+
+        let mut total = 0.0;
+        for (i, cluster) in clusters.iter().enumerate() {
+            if cluster.len() == 1 {
+                continue;
+            }
+
+            for &element in cluster.elements() {
+                let own = mean_distance(element, cluster);
+                let nearest = clusters.iter().enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, other)| mean_distance(element, other))
+                    .fold(f64::INFINITY, f64::min);
+
+                let spread = own.max(nearest);
+                if spread > 0.0 {
+                    total += (nearest - own) / spread;
+                }
+            }
+        }
+
+        total / self.size() as f64
+    }
+
+    /// Returns the Davies–Bouldin index of a partition, 0 or more (lower is better)
+    /// Mean over the clusters of their highest ratio between the intra-cluster distances of two
+    /// clusters and the distance between their centroids. Two clusters sharing a centroid are not
+    /// separated at all, so the index is then infinite
+    /// - clusters: &[Cluster] - Clusters of the partition
+    pub fn davies_bouldin(&self, clusters: &[Cluster]) -> f64 {
+        let clusters: Vec<&Cluster> = clusters.iter().filter(|c| !c.is_empty()).collect();
+        if clusters.len() < 2 {
+            return 0.0;
+        }
+
+        let spreads: Vec<f64> = clusters.iter().map(|c| self.intra_cluster_distance(c)).collect();
+        let mut total = 0.0;
+        for (i, cluster) in clusters.iter().enumerate() {
+            total += clusters.iter().enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, other)| {
+                    let distance = self.distance(cluster.centroid(), other.centroid());
+                    if distance > 0.0 { (spreads[i] + spreads[j]) / distance } else { f64::INFINITY }
+                })
+                .fold(0.0, f64::max);
+        }
+
+        total / clusters.len() as f64
+    }
+
+    /// Returns the Calinski–Harabasz index of a partition, 0 or more (higher is better)
+    /// Ratio between the dispersion of the centroids around the mean of the data and the
+    /// dispersion of the elements around their centroids, each divided by its degrees of freedom.
+    /// Dispersions are variances, so they are Euclidean whatever the metric of the problem.
+    /// Partitions with every element on its centroid cannot be more compact, so their index is
+    /// infinite
+    /// - clusters: &[Cluster] - Clusters of the partition
+    pub fn calinski_harabasz(&self, clusters: &[Cluster]) -> f64 {
+        let clusters: Vec<&Cluster> = clusters.iter().filter(|c| !c.is_empty()).collect();
+        let k = clusters.len();
+        if k < 2 || k >= self.size() {
+            return 0.0;
+        }
+
+        let mean = self.data.iter().fold(Point::zeros(self.dimension()), |acc, x| acc + x) / self.size() as f64;
+        let mut between = 0.0;
+        let mut within = 0.0;
+        for cluster in clusters.iter() {
+            between += cluster.len() as f64 * (cluster.centroid() - &mean).norm_squared();
+            within += cluster.elements().iter().map(|&e| (&self.data[e] - cluster.centroid()).norm_squared()).sum::<f64>();
+        }
+
+        if within == 0.0 {
+            return f64::INFINITY;
+        }
+
+        (between / (k - 1) as f64) / (within / (self.size() - k) as f64)
+    }
+
+    /// Returns the infeasibility increment of inserting an element into a cluster: the weighted sum
+    /// of the constraints of the element it would violate
     /// #Arguments
     /// - element: i32 - Index of an element
     /// - clu: &Cluster - Cluster to check
-    pub fn inf_insert(&self, element: usize, new_cluster: usize, cluster_index: &BTreeMap<usize, usize>) -> usize {
-        let mut inf = 0;
-
-        for ((_, second), con_value) in self.constraints.iter().filter(|((first, _), _)| *first == element) {
-            let cannot_link = *con_value == -1 && cluster_index.get(&second) == Some(&new_cluster);
-            let must_link = *con_value == 1 && cluster_index.get(&second) != Some(&new_cluster);
-            if cannot_link || must_link {
-                inf += 1;
-            }
-        }
-        
+    pub fn inf_insert(&self, element: usize, new_cluster: usize, cluster_index: &BTreeMap<usize, usize>) -> f64 {
+        let cannot_link: f64 = self.cannot_link[element].iter()
+            .filter(|(other, _)| cluster_index.get(other) == Some(&new_cluster))
+            .map(|(_, weight)| weight)
+            .sum();
+        let must_link: f64 = self.must_link[element].iter()
+            .filter(|(other, _)| cluster_index.get(other) != Some(&new_cluster))
+            .map(|(_, weight)| weight)
+            .sum();
+
         // Return value
-        inf
+        cannot_link * self.penalties.1 + must_link * self.penalties.0
+    }
+
+    /// Returns the constraints of an element as (other element, kind, penalty) tuples, where the
+    /// penalty is the weight of the constraint times the multiplier of its kind
+    /// - element: usize - Index of an element
+    pub fn constraints_of(&self, element: usize) -> impl Iterator<Item = (usize, ConstraintKind, f64)> + '_ {
+        let (must_link_penalty, cannot_link_penalty) = self.penalties;
+        let must_link = self.must_link[element].iter()
+            .map(move |&(other, weight)| (other, ConstraintKind::MustLink, weight * must_link_penalty));
+        let cannot_link = self.cannot_link[element].iter()
+            .map(move |&(other, weight)| (other, ConstraintKind::CannotLink, weight * cannot_link_penalty));
+
+        must_link.chain(cannot_link)
+    }
+
+    /// Returns the number of constraints of the problem
+    pub fn constraint_number(&self) -> usize {
+        self.constraint_number
     }
 
     /// Calculates the new centroid of a cluster
@@ -168,33 +682,32 @@ impl Problem {
         cluster.elements().iter().fold(Point::zeros(cluster.dimension()), |acc, x| acc + &self.data[*x]) / cluster.elements().len() as f64
     }
 
-    /// Calculates the infeasibility of a given partition
-    pub fn calc_infeasiblity(&self, cluster_index: &BTreeMap<usize, usize>) -> usize {
-        let mut inf = 0;
-
-        for constraint in self.constraints.iter() {
-            let (e1, e2) = constraint.0;
-            if e1 > e2 {
-                match constraint.1 {
-                    1 if cluster_index.get(e1).unwrap() != cluster_index.get(e2).unwrap() => {
-                        inf += 1;
-                    },
-                    -1 if cluster_index.get(e1).unwrap() == cluster_index.get(e2).unwrap() => {
-                        inf += 1;
-                    },
-                    _ => {}
-                }
-            }
+    /// Calculates the infeasibility of a given partition: the weighted sum of its violated
+    /// constraints, with the multipliers of must-links and cannot-links
+    pub fn calc_infeasiblity(&self, cluster_index: &BTreeMap<usize, usize>) -> f64 {
+        let mut must_link = 0.0;
+        let mut cannot_link = 0.0;
+
+        // Every constraint is stored for both elements, so only count it from the lowest one
+        for (e1, cluster) in cluster_index.iter() {
+            must_link += self.must_link[*e1].iter()
+                .filter(|(e2, _)| e2 > e1 && cluster_index.get(e2) != Some(cluster))
+                .map(|(_, weight)| weight)
+                .sum::<f64>();
+            cannot_link += self.cannot_link[*e1].iter()
+                .filter(|(e2, _)| e2 > e1 && cluster_index.get(e2) == Some(cluster))
+                .map(|(_, weight)| weight)
+                .sum::<f64>();
         }
 
-        inf
+        must_link * self.penalties.0 + cannot_link * self.penalties.1
     }
 
     /// Returns the fitness of a given partition
     /// - partition: &Partition - Partition to evaluate
     pub fn fitness(&self, partition: &Partition) -> f64 {
         self.general_deviation(partition.clusters()) + 
-            self.calc_infeasiblity(partition.cluster_index()) as f64 * self.lambda()
+            self.calc_infeasiblity(partition.cluster_index()) * self.lambda()
     }
 }
 
@@ -203,9 +716,216 @@ impl Display for Problem {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         let s = format!("Problem{{\n\tData: [\n\t{0} elements\n],\nConstraints: [\n\t{1} constraints\n]\n}}",
             self.data.len(),
-            self.constraints.len(),
+            self.constraint_number,
         );
 
         write!(f, "{}", s)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::testing::{TempFile, assert_close};
+
+    /// Problem over one-dimensional points, without constraints
+    fn line(values: &[f64], k: usize) -> Problem {
+        Problem::from_points(values.iter().map(|&v| Point::from_vec(vec![v])).collect(), &[], k)
+    }
+
+    /// Loads a problem from the contents of its files
+    fn load(name: &str, data: &str, constraints: &str, k: usize) -> std::result::Result<Problem, ProblemError> {
+        let data = TempFile::with_content(&format!("{}.dat", name), data);
+        let constraints = TempFile::with_content(name, constraints);
+        Problem::load(data.path(), constraints.path(), k)
+    }
+
+    /// Loads a problem of four points on a line with the given constraints file
+    fn load_constraints(name: &str, constraints: &str) -> std::result::Result<Problem, ProblemError> {
+        load(name, "0\n1\n2\n3\n", constraints, 2)
+    }
+
+    #[test]
+    fn internal_indices_of_two_separated_clusters() {
+        // {0, 2} with centroid 1 and {10, 12} with centroid 11
+        let problem = line(&[0.0, 2.0, 10.0, 12.0], 2);
+        let partition = Partition::from_labels(&[0, 0, 1, 1], &problem);
+
+        // Silhouettes: 9/11 for 0 and 12, 7/9 for 2 and 10
+        assert_close(problem.silhouette(partition.clusters()), 79.0 / 99.0);
+        // Both clusters have an intra-cluster distance of 1 and their centroids are at 10
+        assert_close(problem.davies_bouldin(partition.clusters()), 0.2);
+        // Between: 2·5² + 2·5² = 100 over 1 degree of freedom; within: 4 over 2
+        assert_close(problem.calinski_harabasz(partition.clusters()), 50.0);
+    }
+
+    #[test]
+    fn internal_indices_of_uneven_clusters() {
+        // {0, 1, 2} with centroid 1 and {6} with centroid 6
+        let problem = line(&[0.0, 1.0, 2.0, 6.0], 2);
+        let partition = Partition::from_labels(&[0, 0, 0, 1], &problem);
+
+        // Silhouettes: 0 → (6 - 1.5) / 6, 1 → (5 - 1) / 5, 2 → (4 - 1.5) / 4, and 0 for 6 alone
+        let expected = (4.5 / 6.0 + 4.0 / 5.0 + 2.5 / 4.0) / 4.0;
+        assert_close(problem.silhouette(partition.clusters()), expected);
+        // Intra-cluster distances 2/3 and 0, centroids at 5
+        assert_close(problem.davies_bouldin(partition.clusters()), (2.0 / 3.0) / 5.0);
+        // Mean 2.25: between 3·1.25² + 3.75² = 18.75 over 1 degree of freedom, within 2 over 2
+        assert_close(problem.calinski_harabasz(partition.clusters()), 18.75);
+    }
+
+    #[test]
+    fn davies_bouldin_is_infinite_for_clusters_sharing_a_centroid() {
+        // {0, 2} and {1} both have their centroid at 1
+        let problem = line(&[0.0, 2.0, 1.0], 2);
+        let partition = Partition::from_labels(&[0, 0, 1], &problem);
+
+        assert_eq!(problem.davies_bouldin(partition.clusters()), f64::INFINITY);
+    }
+
+    #[test]
+    fn calinski_harabasz_is_infinite_for_elements_on_their_centroids() {
+        let problem = line(&[0.0, 0.0, 5.0, 5.0], 2);
+        let partition = Partition::from_labels(&[0, 0, 1, 1], &problem);
+
+        assert_eq!(problem.calinski_harabasz(partition.clusters()), f64::INFINITY);
+    }
+
+    #[test]
+    fn internal_indices_of_a_single_cluster() {
+        let problem = line(&[0.0, 1.0, 2.0], 2);
+        let partition = Partition::from_labels(&[0, 0, 0], &problem);
+
+        assert_eq!(problem.silhouette(partition.clusters()), 0.0);
+        assert_eq!(problem.davies_bouldin(partition.clusters()), 0.0);
+        assert_eq!(problem.calinski_harabasz(partition.clusters()), 0.0);
+    }
+
+    #[test]
+    fn constraint_lists_separated_by_commas_or_whitespace() {
+        let commas = load_constraints("commas.txt", "0,1,1\n2,3,-1\n").unwrap();
+        let spaces = load_constraints("spaces.txt", "0 1 1\n2\t3   -1\n").unwrap();
+
+        for problem in [commas, spaces] {
+            assert_eq!(problem.constraint_number(), 2);
+            let constraints: Vec<_> = problem.constraints_of(2).collect();
+            assert_eq!(constraints, vec![(3, ConstraintKind::CannotLink, 1.0)]);
+        }
+    }
+
+    #[test]
+    fn constraint_format_detection() {
+        assert_eq!(ConstraintFormat::detect("c.list", "1,0,0,0", 4), ConstraintFormat::List);
+        assert_eq!(ConstraintFormat::detect("c.const", "0,1,1", 4), ConstraintFormat::List);
+        assert_eq!(ConstraintFormat::detect("c.const", "0 1 1", 3), ConstraintFormat::List);
+        assert_eq!(ConstraintFormat::detect("c.const", "1,0,-1", 3), ConstraintFormat::Matrix);
+        assert_eq!(ConstraintFormat::detect("c.const", "1,0,0,0", 4), ConstraintFormat::Matrix);
+    }
+
+    #[test]
+    fn constraint_lists_reject_repeated_pairs() {
+        match load_constraints("duplicate.list", "0,1,1\n2,3,-1\n1,0,2\n") {
+            Err(ProblemError::DuplicateConstraint { line: 3, first: 1, pair: (1, 0), .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("contradictory.list", "0 1 1\n1 0 -1\n") {
+            Err(ProblemError::ContradictoryConstraint { line: 2, first: 1, pair: (1, 0), .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("self.list", "0,1,1\n2,2,1\n") {
+            Err(ProblemError::SelfConstraint { line: 2, element: 2, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn data_files_must_be_well_formed() {
+        match load("ragged.const", "0,0\n1\n", "1,0\n0,1\n", 2) {
+            Err(ProblemError::RaggedRow { line: 2, expected: 2, found: 1, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load("number.const", "0,0\n1,a\n", "1,0\n0,1\n", 2) {
+            Err(ProblemError::Parse { line: 2, column: 2, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        for coordinate in ["nan", "inf", "-inf", "NaN"] {
+            match load("finite.const", &format!("0,0\n1,{}\n", coordinate), "1,0\n0,1\n", 2) {
+                Err(ProblemError::InvalidCoordinate { line: 2, column: 2, value, .. }) if !value.is_finite() => (),
+                other => panic!("unexpected result {:?}", other.map(|_| ())),
+            }
+        }
+        match load("empty.const", "\n\n", "", 2) {
+            Err(ProblemError::EmptyData { .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match Problem::load("/nonexistent/data.dat", "/nonexistent/data.const", 2) {
+            Err(ProblemError::Io { .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn clusters_must_fit_the_data() {
+        for k in [0, 5] {
+            match load(&format!("clusters{}.list", k), "0\n1\n2\n3\n", "0,1,1\n", k) {
+                Err(ProblemError::Clusters { size: 4, .. }) => (),
+                other => panic!("unexpected result {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn constraint_matrices_must_be_square_and_symmetric() {
+        match load_constraints("rows.const", "1,0,0,0\n0,1,0,0\n0,0,1,0\n") {
+            Err(ProblemError::Dimension { expected: 4, found: 3, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("columns.const", "1,0,0,0\n0,1,0\n0,0,1,0\n0,0,0,1\n") {
+            Err(ProblemError::RaggedRow { line: 2, expected: 4, found: 3, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("asymmetric.const", "1,1,0,0\n-1,1,0,0\n0,0,1,0\n0,0,0,1\n") {
+            Err(ProblemError::Asymmetric { line: 2, column: 1, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("infinite.const", "1,inf,0,0\ninf,1,0,0\n0,0,1,0\n0,0,0,1\n") {
+            Err(ProblemError::InvalidConstraint { line: 1, column: 2, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn constraint_lists_must_reference_elements_with_nonzero_values() {
+        match load_constraints("range.list", "0,1,1\n1,4,-1\n") {
+            Err(ProblemError::IndexOutOfRange { line: 2, column: 2, index: 4, size: 4, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("zero.list", "0,1,0\n") {
+            Err(ProblemError::InvalidConstraint { line: 1, column: 3, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+        match load_constraints("fields.list", "0,1\n") {
+            Err(ProblemError::RaggedRow { line: 1, expected: 3, found: 2, .. }) => (),
+            other => panic!("unexpected result {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn weighted_matrices_and_lists_load_the_same_constraints() {
+        let matrix = load_constraints("weighted.const", "1,2.5,0,0\n2.5,1,0,0\n0,0,1,-0.5\n0,0,-0.5,1\n").unwrap();
+        let list = load_constraints("weighted.list", "0,1,2.5\n3,2,-0.5\n").unwrap();
+
+        for mut problem in [matrix, list] {
+            assert_eq!(problem.constraint_number(), 2);
+            assert_eq!(problem.constraints_of(0).collect::<Vec<_>>(), vec![(1, ConstraintKind::MustLink, 2.5)]);
+            assert_eq!(problem.constraints_of(3).collect::<Vec<_>>(), vec![(2, ConstraintKind::CannotLink, 0.5)]);
+
+            // Both constraints violated, each weight multiplied by the penalty of its kind
+            problem.set_penalties(2.0, 0.5).unwrap();
+            let partition = Partition::from_labels(&[0, 1, 1, 1], &problem);
+            assert_close(problem.calc_infeasiblity(partition.cluster_index()), 2.5 * 2.0 + 0.5 * 0.5);
+        }
+
+        assert!(load_constraints("penalty.list", "0,1,1\n").unwrap().set_penalties(-1.0, 1.0).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use super::{Problem, Partition};
+
+
+/// Kind of a pairwise constraint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConstraintKind {
+    /// Both elements must be in the same cluster
+    MustLink,
+    /// The elements cannot be in the same cluster
+    CannotLink,
+}
+
+/// Constraint violated by a partition
+/// - first: usize - Lowest element of the pair
+/// - second: usize - Highest element of the pair
+/// - kind: ConstraintKind - Kind of the constraint
+/// - clusters: (usize, usize) - Clusters of the first and second elements
+/// - penalty: f64 - Weight of the constraint times the multiplier of its kind
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Violation {
+    pub first: usize,
+    pub second: usize,
+    pub kind: ConstraintKind,
+    pub clusters: (usize, usize),
+    pub penalty: f64,
+}
+
+/// Scores of a cluster of a partition
+/// - size: usize - Number of elements
+/// - intra_cluster_distance: f64 - Mean distance of the elements to the centroid
+/// - must_link_violations: usize - Violated must-links with an element in the cluster
+/// - cannot_link_violations: usize - Violated cannot-links between elements of the cluster
+#[derive(Clone, Copy, Debug)]
+pub struct ClusterReport {
+    pub size: usize,
+    pub intra_cluster_distance: f64,
+    pub must_link_violations: usize,
+    pub cannot_link_violations: usize,
+}
+
+/// Scores of a partition of a problem
+/// - aggregate: f64 - Fitness of the partition
+/// - infeasibility: f64 - Weighted sum of the violated constraints
+/// - general_deviation: f64 - Mean intra-cluster distance of the clusters
+/// - clusters: Vec<ClusterReport> - Scores of each cluster
+/// - violations: Vec<Violation> - Violated constraints, sorted by their elements
+#[derive(Clone, Debug)]
+pub struct PartitionReport {
+    pub aggregate: f64,
+    pub infeasibility: f64,
+    pub general_deviation: f64,
+    pub clusters: Vec<ClusterReport>,
+    pub violations: Vec<Violation>,
+}
+
+impl Problem {
+    /// Scores a partition, breaking its scores down by cluster and listing its violated constraints
+    /// The aggregate, infeasibility and general deviation are the ones of `Problem::fitness`,
+    /// `Problem::calc_infeasiblity` and `Problem::general_deviation`
+    /// - partition: &Partition - Partition to score, which may come from another tool
+    pub fn report(&self, partition: &Partition) -> PartitionReport {
+        let cluster_index = partition.cluster_index();
+        let cluster_of = |element: usize| cluster_index[&element];
+
+        // Every constraint is stored for both elements, so only check it from the lowest one
+        let mut violations = Vec::new();
+        for first in 0..self.size() {
+            for (second, kind, penalty) in self.constraints_of(first).filter(|&(other, _, _)| other > first) {
+                let clusters = (cluster_of(first), cluster_of(second));
+
+                let violated = match kind {
+                    ConstraintKind::MustLink => clusters.0 != clusters.1,
+                    ConstraintKind::CannotLink => clusters.0 == clusters.1,
+                };
+                if violated {
+                    violations.push(Violation { first, second, kind, clusters, penalty });
+                }
+            }
+        }
+        violations.sort_by_key(|v| (v.first, v.second));
+
+        let mut clusters: Vec<ClusterReport> = partition.clusters().iter()
+            .map(|cluster| ClusterReport {
+                size: cluster.len(),
+                intra_cluster_distance: self.intra_cluster_distance(cluster),
+                must_link_violations: 0,
+                cannot_link_violations: 0,
+            })
+            .collect();
+
+        for violation in violations.iter() {
+            let (a, b) = violation.clusters;
+            match violation.kind {
+                ConstraintKind::MustLink => {
+                    clusters[a].must_link_violations += 1;
+                    clusters[b].must_link_violations += 1;
+                },
+                ConstraintKind::CannotLink => clusters[a].cannot_link_violations += 1,
+            }
+        }
+
+        PartitionReport {
+            aggregate: self.fitness(partition),
+            infeasibility: self.calc_infeasiblity(cluster_index),
+            general_deviation: self.general_deviation(partition.clusters()),
+            clusters,
+            violations,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::Point;
+    use crate::par::testing::assert_close;
+
+    #[test]
+    fn violations_are_counted_in_the_clusters_they_involve() {
+        let points = (0..6).map(|i| Point::from_vec(vec![i as f64])).collect();
+        let constraints = [(0, 1, 1.0), (0, 2, 1.0), (1, 3, -1.0), (3, 5, 2.0), (4, 5, -1.0)];
+        let problem = Problem::from_points(points, &constraints, 3);
+        let partition = Partition::from_labels(&[0, 0, 1, 1, 2, 2], &problem);
+
+        let report = problem.report(&partition);
+
+        let pairs: Vec<(usize, usize, ConstraintKind)> = report.violations.iter().map(|v| (v.first, v.second, v.kind)).collect();
+        assert_eq!(pairs, vec![
+            (0, 2, ConstraintKind::MustLink),
+            (3, 5, ConstraintKind::MustLink),
+            (4, 5, ConstraintKind::CannotLink),
+        ]);
+        assert_eq!(report.violations[1].clusters, (1, 2));
+
+        // Must-links count in both clusters, cannot-links in the one they share
+        let counts: Vec<(usize, usize)> = report.clusters.iter().map(|c| (c.must_link_violations, c.cannot_link_violations)).collect();
+        assert_eq!(counts, vec![(1, 0), (2, 0), (1, 1)]);
+        assert!(report.clusters.iter().all(|c| c.size == 2));
+
+        assert_close(report.infeasibility, report.violations.iter().map(|v| v.penalty).sum());
+        assert_close(report.aggregate, problem.fitness(&partition));
+    }
+
+    #[test]
+    fn feasible_partitions_have_no_violations() {
+        let points = (0..4).map(|i| Point::from_vec(vec![i as f64])).collect();
+        let problem = Problem::from_points(points, &[(0, 1, 1.0), (1, 2, -1.0)], 2);
+        let partition = Partition::from_labels(&[0, 0, 1, 1], &problem);
+
+        let report = problem.report(&partition);
+
+        assert!(report.violations.is_empty());
+        assert!(report.clusters.iter().all(|c| c.must_link_violations == 0 && c.cannot_link_violations == 0));
+        assert_eq!(report.infeasibility, 0.0);
+    }
+}
@@ -0,0 +1,56 @@
+use super::{Problem, Partition, TracePoint};
+use std::time::Duration;
+
+
+/// Outcome of an execution of an algorithm
+/// - partition: Partition - Best partition found
+/// - aggregate: f64 - Fitness of the partition
+/// - infeasibility: f64 - Weighted sum of the constraints violated by the partition
+/// - general_deviation: f64 - Mean intra-cluster distance of the partition
+/// - evaluations: usize - Fitness evaluations spent
+/// - iterations: usize - Iterations of the algorithm (generations, cooling levels, embedded searches...)
+/// - restarts: usize - Times the algorithm started again from scratch (greedy COPKM ending with empty clusters)
+/// - accepted_worse: Option<usize> - Moves to a worse solution accepted, for the algorithms based on simulated annealing
+/// - time: Duration - Wall time of the execution
+/// - trace: Option<Vec<TracePoint>> - Convergence trace, if it was recorded
+#[derive(Clone)]
+pub struct SolveResult {
+    pub partition: Partition,
+    pub aggregate: f64,
+    pub infeasibility: f64,
+    pub general_deviation: f64,
+    pub evaluations: usize,
+    pub iterations: usize,
+    pub restarts: usize,
+    pub accepted_worse: Option<usize>,
+    pub time: Duration,
+    pub trace: Option<Vec<TracePoint>>,
+}
+
+impl SolveResult {
+    /// Builds the result of an execution, calculating the infeasibility and general deviation of
+    /// the partition
+    /// - problem: &Problem - Instance of a problem
+    /// - partition: Partition - Best partition found
+    /// - aggregate: f64 - Fitness of the partition
+    /// - evaluations: usize - Fitness evaluations spent
+    /// - iterations: usize - Iterations of the algorithm
+    /// - time: Duration - Wall time of the execution
+    pub fn new(problem: &Problem, partition: Partition, aggregate: f64, evaluations: usize, iterations: usize, time: Duration) -> SolveResult {
+        let infeasibility = problem.calc_infeasiblity(partition.cluster_index());
+        let general_deviation = problem.general_deviation(partition.clusters());
+
+        SolveResult {
+            partition,
+            aggregate,
+            infeasibility,
+            general_deviation,
+            evaluations,
+            iterations,
+            restarts: 0,
+            accepted_worse: None,
+            time,
+            trace: None,
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use super::{Problem, SolveResult, Observer, NoObserver, Trace, Embedded};
+use super::algorithms::{greedy, local_search, generational_genetic, steady_state_genetic, memetic, simulated_annealing,
+    multi_start_local_search, iterated_local_search, iterated_annealing,
+    Crossover, GreedyParams, GeneticParams, MemeticParams, AnnealingParams, MultiStartParams, IteratedParams};
+use rand_pcg::Pcg64;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_derive::{Serialize, Deserialize};
+use std::time::Instant;
+
+
+/// Metaheuristic for the PAR problem, configured with its parameters
+/// Solvers are shared between threads to execute several seeds at the same time
+pub trait Solver: Send + Sync {
+    /// Name of the algorithm
+    fn name(&self) -> &str;
+
+    /// Parameters of the algorithm
+    fn parameters(&self) -> toml::Table;
+
+    /// Executes the algorithm over a problem
+    /// - problem: &par::Problem - Instance of a problem
+    /// - rng: &mut rand_pcg::Pcg64 - Random number generator
+    fn solve(&self, problem: &Problem, rng: &mut Pcg64) -> SolveResult {
+        self.solve_observed(problem, &mut NoObserver, rng)
+    }
+
+    /// Executes the algorithm over a problem, showing its progress to an observer
+    /// - problem: &par::Problem - Instance of a problem
+    /// - observer: &mut dyn Observer - Observer of the execution
+    /// - rng: &mut rand_pcg::Pcg64 - Random number generator
+    fn solve_observed(&self, problem: &Problem, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult;
+
+    /// Executes the algorithm over a problem, recording its convergence trace in the result
+    /// - problem: &par::Problem - Instance of a problem
+    /// - interval: usize - Evaluations between two points of the trace
+    /// - rng: &mut rand_pcg::Pcg64 - Random number generator
+    fn solve_traced(&self, problem: &Problem, interval: usize, rng: &mut Pcg64) -> SolveResult {
+        let mut trace = Trace::new(interval);
+        let mut result = self.solve_observed(problem, &mut trace, rng);
+        trace.finish(&result);
+        result.trace = Some(trace.into_points());
+
+        result
+    }
+}
+
+/// Builds a solver from a maximum number of fitness evaluations and parameters that replace the default ones
+pub type Constructor = fn(Option<usize>, Option<&toml::Table>) -> Result<Box<dyn Solver>, String>;
+
+/// Solver made of an algorithm function and its parameters
+/// - name: String - Name of the algorithm
+/// - params: P - Parameters of the algorithm
+/// - run: Algorithm<P> - Algorithm
+pub struct Configured<P> {
+    name: String,
+    params: P,
+    run: Algorithm<P>,
+}
+
+/// Algorithm function with parameters of type `P`
+pub type Algorithm<P> = fn(&Problem, &P, &mut dyn Observer, &mut Pcg64) -> SolveResult;
+
+impl<P> Configured<P> {
+    /// Creates a solver that runs `run` with the given parameters
+    pub fn new(name: &str, params: P, run: Algorithm<P>) -> Configured<P> {
+        Configured {
+            name: name.to_string(),
+            params,
+            run,
+        }
+    }
+
+    /// Parameters of the algorithm
+    pub fn params(&self) -> &P {
+        &self.params
+    }
+}
+
+impl<P: Serialize + Send + Sync> Solver for Configured<P> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn parameters(&self) -> toml::Table {
+        toml::Table::try_from(&self.params).unwrap_or_default()
+    }
+
+    fn solve_observed(&self, problem: &Problem, observer: &mut dyn Observer, rng: &mut Pcg64) -> SolveResult {
+        // Time the whole execution, in case the algorithm runs something before timing itself
+        let start = Instant::now();
+        let mut result = (self.run)(problem, &self.params, observer, rng);
+        result.time = start.elapsed();
+
+        result
+    }
+}
+
+/// Parameters of the local search started from a greedy solution
+/// - max_evaluations: Option<usize> - Maximum number of fitness evaluations, unbounded if None
+/// - greedy: GreedyParams - Parameters of the greedy COPKM that builds the initial solution
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LocalSearchParams {
+    pub max_evaluations: Option<usize>,
+    pub greedy: GreedyParams,
+}
+
+/// Maps algorithm names to the constructors of their solvers
+pub struct Registry {
+    constructors: Vec<(&'static str, Constructor)>,
+}
+
+impl Registry {
+    /// Creates a registry without algorithms
+    pub fn new() -> Registry {
+        Registry {
+            constructors: Vec::new(),
+        }
+    }
+
+    /// Adds an algorithm, replacing the one with the same name if there is any
+    pub fn register(&mut self, name: &'static str, constructor: Constructor) {
+        match self.constructors.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = constructor,
+            None => self.constructors.push((name, constructor)),
+        }
+    }
+
+    /// Names of the registered algorithms, in registration order
+    pub fn names(&self) -> Vec<&'static str> {
+        self.constructors.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Whether an algorithm with the given name has been registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.iter().any(|(n, _)| *n == name)
+    }
+
+    /// Builds the solver of an algorithm
+    /// - name: &str - Name of the algorithm
+    /// - budget: Option<usize> - Maximum number of fitness evaluations, if different from the default one
+    /// - params: Option<&toml::Table> - Parameters that replace the default ones of the algorithm
+    pub fn create(&self, name: &str, budget: Option<usize>, params: Option<&toml::Table>) -> Result<Box<dyn Solver>, String> {
+        match self.constructors.iter().find(|(n, _)| *n == name) {
+            Some((_, constructor)) => constructor(budget, params),
+            None => Err(format!("unknown algorithm '{}' (available: {})", name, self.names().join(", "))),
+        }
+    }
+}
+
+impl Default for Registry {
+    /// Registry with every algorithm of the crate
+    fn default() -> Registry {
+        let mut registry = Registry::new();
+
+        registry.register("greedy", |_, params| {
+            let params = with_params(GreedyParams::default(), params)?;
+            Ok(Box::new(Configured::new("greedy", params, greedy)))
+        });
+        registry.register("local-search", |budget, params| {
+            let params = with_params(LocalSearchParams::default(), params)?;
+            let params = LocalSearchParams { max_evaluations: budget.or(params.max_evaluations), ..params };
+            Ok(Box::new(Configured::new("local-search", params, |p, params, observer, rng| {
+                let initial = greedy(p, &params.greedy, observer, rng);
+                let mut embedded = Embedded::new(observer, initial.evaluations, initial.aggregate);
+                let max_evaluations = params.max_evaluations.unwrap_or(usize::MAX);
+                let mut result = local_search(p, initial.partition, max_evaluations, &mut embedded, rng);
+                result.evaluations += initial.evaluations;
+                result.restarts = initial.restarts;
+                result
+            })))
+        });
+        registry.register("agg-un", |budget, params| {
+            let params = genetic_params(Crossover::Uniform, 0.7, budget, params)?;
+            Ok(Box::new(Configured::new("agg-un", params, generational_genetic)))
+        });
+        registry.register("agg-sf", |budget, params| {
+            let params = genetic_params(Crossover::FixedSegment, 0.7, budget, params)?;
+            Ok(Box::new(Configured::new("agg-sf", params, generational_genetic)))
+        });
+        registry.register("age-un", |budget, params| {
+            let params = genetic_params(Crossover::Uniform, 1.0, budget, params)?;
+            Ok(Box::new(Configured::new("age-un", params, steady_state_genetic)))
+        });
+        registry.register("age-sf", |budget, params| {
+            let params = genetic_params(Crossover::FixedSegment, 1.0, budget, params)?;
+            Ok(Box::new(Configured::new("age-sf", params, steady_state_genetic)))
+        });
+        registry.register("am-10-1.0", |budget, params| {
+            let params = memetic_params(MemeticParams::all(), budget, params)?;
+            Ok(Box::new(Configured::new("am-10-1.0", params, memetic)))
+        });
+        registry.register("am-10-0.1", |budget, params| {
+            let params = memetic_params(MemeticParams::random(), budget, params)?;
+            Ok(Box::new(Configured::new("am-10-0.1", params, memetic)))
+        });
+        registry.register("am-10-0.1mej", |budget, params| {
+            let params = memetic_params(MemeticParams::best(), budget, params)?;
+            Ok(Box::new(Configured::new("am-10-0.1mej", params, memetic)))
+        });
+        registry.register("es", |budget, params| {
+            let params = with_params(AnnealingParams::default(), params)?;
+            let params = AnnealingParams { max_evaluations: budget.unwrap_or(params.max_evaluations), ..params };
+            params.validate()?;
+            Ok(Box::new(Configured::new("es", params, simulated_annealing)))
+        });
+        registry.register("bmb", |budget, params| {
+            let params = with_params(MultiStartParams::default(), params)?;
+            let params = MultiStartParams { max_evaluations: budget.unwrap_or(params.max_evaluations), ..params };
+            params.validate()?;
+            Ok(Box::new(Configured::new("bmb", params, multi_start_local_search)))
+        });
+        registry.register("ils", |budget, params| {
+            let params = iterated_params(budget, params)?;
+            Ok(Box::new(Configured::new("ils", params, iterated_local_search)))
+        });
+        registry.register("ils-es", |budget, params| {
+            let params = iterated_params(budget, params)?;
+            Ok(Box::new(Configured::new("ils-es", params, iterated_annealing)))
+        });
+
+        registry
+    }
+}
+
+/// Parameters of a genetic algorithm with the given crossover
+fn genetic_params(crossover: Crossover, crossover_probability: f64, budget: Option<usize>, params: Option<&toml::Table>) -> Result<GeneticParams, String> {
+    let params = with_params(GeneticParams { crossover, crossover_probability, ..GeneticParams::default() }, params)?;
+    let params = GeneticParams { max_evaluations: budget.unwrap_or(params.max_evaluations), ..params };
+    params.validate()?;
+    Ok(params)
+}
+
+/// Parameters of a memetic algorithm, the budget being the one of its genetic algorithm
+fn memetic_params(defaults: MemeticParams, budget: Option<usize>, params: Option<&toml::Table>) -> Result<MemeticParams, String> {
+    let params = with_params(defaults, params)?;
+    let genetic = GeneticParams { max_evaluations: budget.unwrap_or(params.genetic.max_evaluations), ..params.genetic };
+    let params = MemeticParams { genetic, ..params };
+    params.validate()?;
+    Ok(params)
+}
+
+/// Parameters of an iterated search, the budget being split between its searches
+fn iterated_params(budget: Option<usize>, params: Option<&toml::Table>) -> Result<IteratedParams, String> {
+    let params = with_params(IteratedParams::default(), params)?;
+    params.validate()?;
+    let max_evaluations = budget.map_or(params.max_evaluations, |b| b / params.iterations);
+    Ok(IteratedParams { max_evaluations, ..params })
+}
+
+/// Returns `defaults` with the values present in `overrides` replaced
+pub fn with_params<T: Serialize + DeserializeOwned>(defaults: T, overrides: Option<&toml::Table>) -> Result<T, String> {
+    /// Recursively replaces the values of `table` present in `overrides`
+    fn merge(table: &mut toml::Table, overrides: &toml::Table) {
+        for (key, value) in overrides.iter() {
+            match (table.get_mut(key), value) {
+                (Some(toml::Value::Table(inner)), toml::Value::Table(inner_overrides)) => merge(inner, inner_overrides),
+                _ => {
+                    table.insert(key.clone(), value.clone());
+                },
+            }
+        }
+    }
+
+    match overrides {
+        None => Ok(defaults),
+        Some(overrides) if overrides.is_empty() => Ok(defaults),
+        Some(overrides) => {
+            let mut table = toml::Table::try_from(&defaults).map_err(|e| format!("invalid parameters: {}", e))?;
+            merge(&mut table, overrides);
+            table.try_into().map_err(|e: toml::de::Error| format!("invalid parameters: {}", e))
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_start_rejects_budgets_that_cannot_run_every_search() {
+        let registry = Registry::default();
+        let restarts: toml::Table = toml::from_str("restarts = 0").unwrap();
+
+        assert!(registry.create("bmb", None, Some(&restarts)).is_err());
+        assert!(registry.create("bmb", Some(9), None).is_err());
+        assert!(registry.create("bmb", Some(10), None).is_ok());
+        assert!(registry.create("bmb", None, None).is_ok());
+    }
+
+    #[test]
+    fn annealing_rejects_undefined_cooling_schedules() {
+        let registry = Registry::default();
+        for invalid in ["phi = 1.0", "phi = 0.0", "initial_temperature = -1.0", "final_temperature = 0.0", "mu = 0.0"] {
+            let params: toml::Table = toml::from_str(invalid).unwrap();
+            assert!(registry.create("es", None, Some(&params)).is_err(), "{} was accepted", invalid);
+        }
+
+        let params: toml::Table = toml::from_str("initial_temperature = 10.0\nphi = 0.5").unwrap();
+        assert!(registry.create("es", None, Some(&params)).is_ok());
+    }
+
+    #[test]
+    fn with_params_replaces_only_the_given_values() {
+        let params: toml::Table = toml::from_str("period = 5\ngenetic.population_size = 10").unwrap();
+        let merged = with_params(MemeticParams::best(), Some(&params)).unwrap();
+
+        assert_eq!(merged.period, 5);
+        assert_eq!(merged.genetic.population_size, 10);
+        assert_eq!(merged.genetic.crossover_probability, GeneticParams::default().crossover_probability);
+        assert_eq!(merged.probability, 0.1);
+        assert!(merged.best_only);
+    }
+
+    #[test]
+    fn with_params_keeps_the_defaults_without_overrides() {
+        let empty = toml::Table::new();
+        for overrides in [None, Some(&empty)] {
+            let params = with_params(MemeticParams::random(), overrides).unwrap();
+            assert_eq!(params.probability, 0.1);
+            assert!(!params.best_only);
+        }
+    }
+
+    #[test]
+    fn with_params_rejects_unknown_and_mistyped_fields() {
+        for invalid in ["populaton_size = 10", "genetic.populaton_size = 10", "period = \"often\"", "genetic = 3"] {
+            let params: toml::Table = toml::from_str(invalid).unwrap();
+            assert!(with_params(MemeticParams::all(), Some(&params)).is_err(), "{} was accepted", invalid);
+        }
+    }
+
+    /// Asserts that the registry refuses to build the algorithm with each of the given parameters
+    fn assert_rejected(name: &str, invalid: &[&str]) {
+        let registry = Registry::default();
+        for params in invalid {
+            let params: toml::Table = toml::from_str(params).unwrap();
+            assert!(registry.create(name, None, Some(&params)).is_err(), "{} accepted {}", name, params);
+        }
+    }
+
+    #[test]
+    fn genetic_algorithms_reject_populations_that_cannot_evolve() {
+        let invalid = [
+            "population_size = 0",
+            "population_size = 1",
+            "crossover_probability = 1.5",
+            "mutation_probability = -0.1",
+            "crossover_probability = 0.0\nmutation_probability = 0.0",
+        ];
+        for name in ["agg-un", "agg-sf", "age-un", "age-sf"] {
+            assert_rejected(name, &invalid);
+        }
+    }
+
+    #[test]
+    fn memetic_algorithms_reject_invalid_schedules() {
+        let invalid = ["period = 0", "probability = 2.0", "failures_ratio = -1.0", "genetic.population_size = 1"];
+        for name in ["am-10-1.0", "am-10-0.1", "am-10-0.1mej"] {
+            assert_rejected(name, &invalid);
+        }
+    }
+
+    #[test]
+    fn iterated_searches_reject_invalid_parameters() {
+        let invalid = ["iterations = 0", "segment_ratio = 1.5", "annealing.phi = 1.0"];
+        assert_rejected("ils", &invalid);
+        assert_rejected("ils-es", &invalid);
+    }
+
+    /// Problem with few elements per cluster, so that crossovers often leave clusters empty
+    fn crowded_problem() -> Problem {
+        use crate::par::Point;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = Pcg64::seed_from_u64(3);
+        let points = (0..12).map(|_| Point::from_fn(2, |_, _| rng.gen_range(0.0..1.0))).collect();
+        Problem::from_points(points, &[(0, 1, 1.0), (2, 3, -1.0), (4, 5, -1.0)], 5)
+    }
+
+    /// Checks that every execution of the given algorithms returns a partition without empty clusters
+    fn assert_no_empty_cluster(names: &[&str]) {
+        use rand::SeedableRng;
+
+        let problem = crowded_problem();
+        let registry = Registry::default();
+        for name in names {
+            let solver = registry.create(name, Some(2000), None).unwrap();
+            for seed in 0..5 {
+                let result = solver.solve(&problem, &mut Pcg64::seed_from_u64(seed));
+                assert!(result.partition.clusters().iter().all(|c| !c.is_empty()), "{} left a cluster empty", name);
+                assert_eq!(result.partition.labels().len(), problem.size());
+            }
+        }
+    }
+
+    #[test]
+    fn genetic_partitions_have_no_empty_cluster() {
+        assert_no_empty_cluster(&["agg-un", "agg-sf", "age-un", "age-sf"]);
+    }
+
+    #[test]
+    fn memetic_partitions_have_no_empty_cluster() {
+        assert_no_empty_cluster(&["am-10-1.0", "am-10-0.1", "am-10-0.1mej"]);
+    }
+}
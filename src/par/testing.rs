@@ -0,0 +1,48 @@
+//! Helpers shared by the unit tests of the PAR modules
+use std::fs::{remove_file, write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+
+/// Number of temporary files created, so that tests running at the same time never share one
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+/// File in the temporary directory, removed when it is dropped
+pub struct TempFile {
+    path: String,
+}
+
+impl TempFile {
+    /// Reserves a path in the temporary directory ending in `name`, without creating the file
+    pub fn new(name: &str) -> TempFile {
+        let id = CREATED.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("practicas-{}-{}-{}", std::process::id(), id, name));
+
+        TempFile {
+            path: path.to_str().unwrap().to_string(),
+        }
+    }
+
+    /// Creates a file in the temporary directory with the given content
+    pub fn with_content(name: &str, content: &str) -> TempFile {
+        let file = TempFile::new(name);
+        write(&file.path, content).unwrap();
+        file
+    }
+
+    /// Path of the file
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // The file may not have been created
+        let _ = remove_file(&self.path);
+    }
+}
+
+/// Asserts that two values are equal up to rounding errors
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-12, "{} != {}", actual, expected);
+}
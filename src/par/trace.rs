@@ -0,0 +1,214 @@
+use super::SolveResult;
+use serde_derive::Serialize;
+use std::time::Instant;
+
+
+/// Hook called by the algorithms as they spend fitness evaluations
+pub trait Observer {
+    /// Observes the state of an algorithm
+    /// - evaluations: usize - Fitness evaluations spent so far
+    /// - fitness: f64 - Fitness of the current solution
+    /// - best_fitness: f64 - Fitness of the best solution found so far
+    /// - infeasibility: &dyn Fn() -> f64 - Calculates the infeasibility of the current solution,
+    ///   which may be expensive, so it should only be called when needed
+    fn observe(&mut self, evaluations: usize, fitness: f64, best_fitness: f64, infeasibility: &dyn Fn() -> f64);
+}
+
+/// Observer that ignores everything
+pub struct NoObserver;
+
+impl Observer for NoObserver {
+    fn observe(&mut self, _: usize, _: f64, _: f64, _: &dyn Fn() -> f64) {}
+}
+
+/// State of an algorithm at some point of its execution
+/// - evaluations: usize - Fitness evaluations spent
+/// - time: f64 - Milliseconds since the start of the execution
+/// - fitness: f64 - Fitness of the current solution
+/// - best_fitness: f64 - Fitness of the best solution found so far
+/// - infeasibility: f64 - Infeasibility of the current solution
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TracePoint {
+    pub evaluations: usize,
+    pub time: f64,
+    pub fitness: f64,
+    pub best_fitness: f64,
+    pub infeasibility: f64,
+}
+
+/// Convergence trace: observer that records the state of an algorithm every `interval` evaluations
+/// - interval: usize - Evaluations between two records
+/// - next: usize - Evaluations of the next record
+/// - start: Instant - Start of the execution
+/// - points: Vec<TracePoint> - Recorded states
+pub struct Trace {
+    interval: usize,
+    next: usize,
+    start: Instant,
+    points: Vec<TracePoint>,
+}
+
+impl Trace {
+    /// Creates a trace that records the first observation and then one every `interval` evaluations
+    pub fn new(interval: usize) -> Trace {
+        Trace {
+            interval: interval.max(1),
+            next: 0,
+            start: Instant::now(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Records the final state of an execution, unless it has already been recorded
+    pub fn finish(&mut self, result: &SolveResult) {
+        if self.points.last().is_none_or(|p| p.evaluations < result.evaluations) {
+            self.points.push(TracePoint {
+                evaluations: result.evaluations,
+                time: self.elapsed(),
+                fitness: result.aggregate,
+                best_fitness: result.aggregate,
+                infeasibility: result.infeasibility,
+            });
+        }
+    }
+
+    /// Recorded states, in order
+    pub fn points(&self) -> &[TracePoint] {
+        &self.points
+    }
+
+    /// Consumes the trace, returning its recorded states
+    pub fn into_points(self) -> Vec<TracePoint> {
+        self.points
+    }
+
+    /// Milliseconds since the trace was created
+    fn elapsed(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+impl Observer for Trace {
+    fn observe(&mut self, evaluations: usize, fitness: f64, best_fitness: f64, infeasibility: &dyn Fn() -> f64) {
+        if evaluations < self.next {
+            return;
+        }
+
+        self.points.push(TracePoint {
+            evaluations,
+            time: self.elapsed(),
+            fitness,
+            best_fitness,
+            infeasibility: infeasibility(),
+        });
+        self.next = (evaluations / self.interval + 1) * self.interval;
+    }
+}
+
+/// Observer of a search embedded in another algorithm: shifts the evaluations of the search by
+/// the ones spent before it, and keeps the best fitness of the whole algorithm
+/// - observer: &mut dyn Observer - Observer of the whole algorithm
+/// - offset: usize - Evaluations spent before the search
+/// - best_fitness: f64 - Best fitness found before the search
+pub struct Embedded<'a> {
+    observer: &'a mut dyn Observer,
+    offset: usize,
+    best_fitness: f64,
+}
+
+impl<'a> Embedded<'a> {
+    /// Creates the observer of a search that starts after `offset` evaluations
+    pub fn new(observer: &'a mut dyn Observer, offset: usize, best_fitness: f64) -> Embedded<'a> {
+        Embedded {
+            observer,
+            offset,
+            best_fitness,
+        }
+    }
+}
+
+impl Observer for Embedded<'_> {
+    fn observe(&mut self, evaluations: usize, fitness: f64, best_fitness: f64, infeasibility: &dyn Fn() -> f64) {
+        self.observer.observe(self.offset + evaluations, fitness, best_fitness.min(self.best_fitness), infeasibility);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::par::{Problem, Partition, Point};
+    use std::time::Duration;
+
+    /// Result of an execution that spent `evaluations` evaluations
+    fn result(evaluations: usize) -> SolveResult {
+        let points = (0..4).map(|i| Point::from_vec(vec![i as f64])).collect();
+        let problem = Problem::from_points(points, &[(0, 3, 1.0)], 2);
+        let partition = Partition::from_labels(&[0, 0, 1, 1], &problem);
+        let fitness = problem.fitness(&partition);
+        SolveResult::new(&problem, partition, fitness, evaluations, 1, Duration::ZERO)
+    }
+
+    fn evaluations(trace: &Trace) -> Vec<usize> {
+        trace.points().iter().map(|p| p.evaluations).collect()
+    }
+
+    #[test]
+    fn trace_records_the_first_observation_and_then_each_interval() {
+        let mut trace = Trace::new(10);
+        for e in [1, 5, 9, 10, 11, 23, 24, 30, 45] {
+            trace.observe(e, e as f64, 0.0, &|| 0.5);
+        }
+
+        assert_eq!(evaluations(&trace), vec![1, 10, 23, 30, 45]);
+        assert_eq!(trace.points()[1].fitness, 10.0);
+        assert_eq!(trace.points()[1].infeasibility, 0.5);
+    }
+
+    #[test]
+    fn trace_interval_is_at_least_one() {
+        let mut trace = Trace::new(0);
+        for e in [1, 1, 2, 3] {
+            trace.observe(e, 0.0, 0.0, &|| 0.0);
+        }
+
+        assert_eq!(evaluations(&trace), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn finish_records_the_final_state_only_once() {
+        let final_result = result(25);
+
+        let mut trace = Trace::new(10);
+        trace.observe(1, 3.0, 3.0, &|| 0.0);
+        trace.finish(&final_result);
+        trace.finish(&final_result);
+
+        assert_eq!(evaluations(&trace), vec![1, 25]);
+        let last = trace.points()[1];
+        assert_eq!(last.fitness, final_result.aggregate);
+        assert_eq!(last.best_fitness, final_result.aggregate);
+        assert_eq!(last.infeasibility, final_result.infeasibility);
+
+        let mut recorded = Trace::new(5);
+        recorded.observe(25, 1.0, 1.0, &|| 0.0);
+        recorded.finish(&final_result);
+        assert_eq!(evaluations(&recorded), vec![25]);
+    }
+
+    #[test]
+    fn embedded_searches_are_shifted_and_keep_the_best_fitness() {
+        let mut trace = Trace::new(1);
+        {
+            let mut embedded = Embedded::new(&mut trace, 100, 2.0);
+            embedded.observe(1, 5.0, 4.0, &|| 0.0);
+            embedded.observe(2, 1.5, 1.5, &|| 0.0);
+        }
+
+        assert_eq!(evaluations(&trace), vec![101, 102]);
+        assert_eq!(trace.points()[0].fitness, 5.0);
+        assert_eq!(trace.points()[0].best_fitness, 2.0);
+        assert_eq!(trace.points()[1].best_fitness, 1.5);
+    }
+}